@@ -0,0 +1,40 @@
+//! A stand-in for Discord's gateway that keeps breaking the connection, for checking
+//! how the client heartbeats, resumes and reconnects.
+//!
+//! ```bash
+//! cargo run --example fake_gateway -- 8765 20
+//! OXIDECORD_GATEWAY_URL=ws://127.0.0.1:8765 cargo run
+//! ```
+//!
+//! REST calls still go to Discord, so log in with a real token. Every `interval`
+//! seconds (20 by default) the current connection gets the next fault from `FAULTS`.
+
+// The event log is only read by the gateway tests
+#[allow(dead_code)]
+mod server;
+
+use server::{Fault, Log};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+const FAULTS: [Fault; 7] = [
+    Fault::Drop,
+    Fault::Zombie,
+    Fault::Hang,
+    Fault::Close(4000),
+    Fault::Reconnect,
+    Fault::InvalidSession { resumable: true },
+    Fault::InvalidSession { resumable: false },
+];
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let port = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(8765);
+    let interval = Duration::from_secs(args.next().and_then(|arg| arg.parse().ok()).unwrap_or(20));
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    println!("Fake gateway on ws://127.0.0.1:{}, a fault every {:?}", port, interval);
+
+    server::serve(listener, |connection| Some(FAULTS[connection % FAULTS.len()]), interval, Log::default()).await
+}
@@ -0,0 +1,216 @@
+//! The fake gateway's server, shared by the example and the gateway tests.
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_websockets::{CloseCode, Message, ServerBuilder, WebSocketStream};
+
+/// Short enough that a missed ACK shows up within seconds.
+const HEARTBEAT_INTERVAL_MS: u64 = 5000;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Fault {
+    /// Drops the TCP connection without a close frame, like losing Wi-Fi.
+    Drop,
+    /// Closes with a resumable code.
+    Close(u16),
+    /// Stops acknowledging heartbeats, like a connection that died during sleep.
+    Zombie,
+    /// Never says hello, like a proxy that accepts the connection and stalls.
+    Hang,
+    /// Asks the client to reconnect (opcode 7).
+    Reconnect,
+    /// Invalidates the session (opcode 9).
+    InvalidSession { resumable: bool },
+}
+
+/// Last sequence number sent per session, so resumes can be checked.
+type Sessions = Arc<Mutex<HashMap<String, u64>>>;
+
+/// What a client did, in the order it happened.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Logged {
+    Identify { session_id: String },
+    Resume { session_id: String, accepted: bool },
+}
+
+pub type Log = Arc<Mutex<Vec<Logged>>>;
+
+struct Connection {
+    ws: WebSocketStream<TcpStream>,
+    sessions: Sessions,
+    session_id: Option<String>,
+    port: u16,
+    log: Log,
+}
+
+impl Connection {
+    async fn send(&mut self, payload: Value) -> bool {
+        self.ws.send(Message::text(payload.to_string())).await.is_ok()
+    }
+
+    async fn dispatch(&mut self, event: &str, data: Value) -> bool {
+        let Some(session_id) = self.session_id.clone() else {
+            return true;
+        };
+        let sequence = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let sequence = sessions.entry(session_id).or_default();
+            *sequence += 1;
+            *sequence
+        };
+        self.send(json!({ "op": 0, "t": event, "s": sequence, "d": data })).await
+    }
+
+    async fn identify(&mut self) -> bool {
+        static NEXT_SESSION: AtomicUsize = AtomicUsize::new(1);
+        let session_id = format!("fake-session-{}", NEXT_SESSION.fetch_add(1, Ordering::Relaxed));
+        println!("IDENTIFY, new session {}", session_id);
+        self.sessions.lock().unwrap().insert(session_id.clone(), 0);
+        self.log.lock().unwrap().push(Logged::Identify { session_id: session_id.clone() });
+        self.session_id = Some(session_id.clone());
+
+        let ready = json!({
+            "v": 10,
+            "user": {
+                "id": "1",
+                "username": "fake-gateway",
+                "discriminator": "0",
+                "avatar": null,
+                "bot": true,
+                "mfa_enabled": false,
+            },
+            "guilds": [],
+            "session_id": session_id,
+            "resume_gateway_url": format!("ws://127.0.0.1:{}", self.port),
+            "application": { "id": "1", "flags": 0 },
+            "shard": [0, 1],
+        });
+        self.dispatch("READY", ready).await
+    }
+
+    async fn resume(&mut self, data: &Value) -> bool {
+        let session_id = data["session_id"].as_str().unwrap_or_default().to_string();
+        let sequence = data["seq"].as_u64();
+        let known = self.sessions.lock().unwrap().get(&session_id).copied();
+        println!("RESUME {} at {:?}, last sent {:?}", session_id, sequence, known);
+
+        let accepted = matches!((known, sequence), (Some(last), Some(sequence)) if sequence <= last);
+        self.log.lock().unwrap().push(Logged::Resume { session_id: session_id.clone(), accepted });
+
+        if accepted {
+            self.session_id = Some(session_id);
+            self.dispatch("RESUMED", json!({})).await
+        } else {
+            self.send(json!({ "op": 9, "d": false })).await
+        }
+    }
+
+    /// Applies a fault, returning whether the connection stays open.
+    async fn inject(&mut self, fault: Fault) -> bool {
+        println!("Injecting {:?}", fault);
+        match fault {
+            Fault::Drop => false,
+            Fault::Close(code) => {
+                let code = CloseCode::try_from(code).expect("valid close code");
+                let _ = self.ws.send(Message::close(Some(code), "fake gateway fault")).await;
+                false
+            }
+            Fault::Zombie | Fault::Hang => true,
+            Fault::Reconnect => self.send(json!({ "op": 7, "d": null })).await,
+            Fault::InvalidSession { resumable } => self.send(json!({ "op": 9, "d": resumable })).await,
+        }
+    }
+
+    async fn run(mut self, mut fault: Option<Fault>, interval: Duration) {
+        if let Some(Fault::Hang) = fault {
+            println!("Injecting Hang");
+            while let Some(Ok(_)) = self.ws.next().await {}
+            return;
+        }
+        if !self.send(json!({ "op": 10, "d": { "heartbeat_interval": HEARTBEAT_INTERVAL_MS } })).await {
+            return;
+        }
+
+        let fault_timer = tokio::time::sleep(interval);
+        tokio::pin!(fault_timer);
+        let mut zombie = false;
+
+        loop {
+            tokio::select! {
+                _ = &mut fault_timer, if fault.is_some() => {
+                    let Some(fault) = fault.take() else {
+                        continue;
+                    };
+                    zombie = matches!(fault, Fault::Zombie);
+                    if !self.inject(fault).await {
+                        return;
+                    }
+                }
+                message = self.ws.next() => {
+                    let Some(Ok(message)) = message else {
+                        println!("Client disconnected");
+                        return;
+                    };
+                    let Some(payload) = message.as_text().and_then(|text| serde_json::from_str::<Value>(text).ok()) else {
+                        continue;
+                    };
+                    let open = match payload["op"].as_u64() {
+                        Some(1) if zombie => true,
+                        Some(1) => self.send(json!({ "op": 11 })).await,
+                        Some(2) => self.identify().await,
+                        Some(6) => self.resume(&payload["d"]).await,
+                        _ => true,
+                    };
+                    if !open {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Accepts clients on `listener` until it fails. The nth connection gets `faults(n)`,
+/// `interval` after it opens, and lives on normally if that's `None`.
+pub async fn serve(
+    listener: TcpListener,
+    faults: impl Fn(usize) -> Option<Fault> + Send,
+    interval: Duration,
+    log: Log,
+) -> std::io::Result<()> {
+    let port = listener.local_addr()?.port();
+    let sessions = Sessions::default();
+    let mut connections = 0;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let fault = faults(connections);
+        connections += 1;
+        let sessions = sessions.clone();
+        let log = log.clone();
+
+        tokio::spawn(async move {
+            let ws = match ServerBuilder::new().accept(stream).await {
+                Ok((_, ws)) => ws,
+                Err(e) => {
+                    eprintln!("Handshake failed: {:?}", e);
+                    return;
+                }
+            };
+            println!("Client connected");
+            let connection = Connection {
+                ws,
+                sessions,
+                session_id: None,
+                port,
+                log,
+            };
+            connection.run(fault, interval).await;
+        });
+    }
+}
@@ -1,11 +1,26 @@
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use twilight_gateway::MessageSender;
 use twilight_http::Client as HttpClient;
-use twilight_model::id::marker::{GuildMarker, ChannelMarker};
+use twilight_model::channel::message::Component;
+use twilight_model::gateway::presence::Status;
+use twilight_model::id::marker::{
+    ApplicationMarker, CommandMarker, EmojiMarker, GuildMarker, ChannelMarker, MessageMarker, RoleMarker, UserMarker,
+};
+use crate::services::gateway::LARGE_GUILD_MEMBERS;
+use crate::services::connection::ConnectionHealth;
+use crate::services::notifications::{Notification, NotificationSettings, Notifier};
+use crate::services::gateway_config::GatewayConfig;
+use crate::services::presence::PresenceSettings;
+use crate::services::scheduler::PendingFetch;
+use crate::services::storage::Storage;
+use crate::settings::Settings;
 
 #[derive(Clone, PartialEq)]
 pub enum View {
     Login,
-    Servers,
     Channel,
 }
 
@@ -13,6 +28,7 @@ pub enum View {
 pub struct GuildInfo {
     pub id: twilight_model::id::Id<GuildMarker>,
     pub name: String,
+    pub member_count: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -21,6 +37,49 @@ pub struct ChannelInfo {
     pub name: String,
 }
 
+#[derive(Clone)]
+pub struct RoleInfo {
+    pub id: twilight_model::id::Id<RoleMarker>,
+    pub name: String,
+    pub color: u32,
+    pub position: i64,
+    pub hoist: bool,
+}
+
+#[derive(Clone)]
+pub struct MemberInfo {
+    pub user_id: twilight_model::id::Id<UserMarker>,
+    pub username: String,
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub roles: Vec<twilight_model::id::Id<RoleMarker>>,
+    pub bot: bool,
+    pub joined_at: Option<DateTime<Utc>>,
+}
+
+/// Account details that aren't part of the member cache, fetched when a profile is opened.
+#[derive(Clone)]
+pub struct UserProfile {
+    pub username: String,
+    pub global_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub accent_color: Option<u32>,
+    pub bot: bool,
+    pub pronouns: Option<String>,
+    pub bio: Option<String>,
+}
+
+/// How much of a guild's member list has been asked for.
+#[derive(Clone, Copy, Default)]
+pub struct MemberPages {
+    /// Highest user id loaded over REST, where the next page starts.
+    pub after: Option<twilight_model::id::Id<UserMarker>>,
+    pub loading: bool,
+    /// Every member has been requested, so there's nothing left to page in.
+    pub complete: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct AttachmentInfo {
     pub url: String,
@@ -50,36 +109,774 @@ pub struct MessageInfo {
     pub author_name: String,
     pub author_id: twilight_model::id::Id<twilight_model::id::marker::UserMarker>,
     pub author_avatar_url: Option<String>,
-    pub timestamp: String,
+    pub author_roles: Vec<twilight_model::id::Id<RoleMarker>>,
+    pub author_color: Option<u32>,
+    pub timestamp: DateTime<Utc>,
     pub attachments: Vec<AttachmentInfo>,
+    pub mentions_me: bool,
+    /// Interaction response only we can see.
+    pub ephemeral: bool,
+    /// "name used a command" for interaction responses.
+    pub interaction: Option<String>,
+    /// Buttons, select menus and layout components sent by bots.
+    pub components: Vec<Component>,
+}
+
+/// A message that pinged us, kept for the mentions inbox.
+#[derive(Clone)]
+pub struct MentionInfo {
+    pub guild_id: Option<twilight_model::id::Id<GuildMarker>>,
+    pub channel_id: twilight_model::id::Id<ChannelMarker>,
+    pub message: MessageInfo,
+}
+
+/// How many mentions the inbox keeps around.
+pub const MENTIONS_INBOX_LIMIT: usize = 100;
+
+/// A guild's custom emoji.
+#[derive(Clone)]
+pub struct EmojiInfo {
+    pub id: twilight_model::id::Id<EmojiMarker>,
+    pub name: String,
+    pub animated: bool,
+}
+
+impl EmojiInfo {
+    /// The `<:name:id>` form used in message content.
+    pub fn markup(&self) -> String {
+        if self.animated {
+            format!("<a:{}:{}>", self.name, self.id)
+        } else {
+            format!("<:{}:{}>", self.name, self.id)
+        }
+    }
+
+    pub fn url(&self) -> String {
+        let ext = if self.animated { "gif" } else { "png" };
+        format!("https://cdn.discordapp.com/emojis/{}.{}", self.id, ext)
+    }
+}
+
+/// An emoji chosen in the picker.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum PickedEmoji {
+    Unicode(String),
+    Custom {
+        id: twilight_model::id::Id<EmojiMarker>,
+        name: String,
+        animated: bool,
+    },
+}
+
+impl From<&EmojiInfo> for PickedEmoji {
+    fn from(emoji: &EmojiInfo) -> Self {
+        PickedEmoji::Custom {
+            id: emoji.id,
+            name: emoji.name.clone(),
+            animated: emoji.animated,
+        }
+    }
+}
+
+/// How many recently used emoji the picker remembers.
+pub const RECENT_EMOJI_LIMIT: usize = 30;
+
+/// One of the bot's application commands in the current guild.
+#[derive(Clone)]
+pub struct CommandInfo {
+    pub id: twilight_model::id::Id<CommandMarker>,
+    /// `None` for global commands.
+    pub guild_id: Option<twilight_model::id::Id<GuildMarker>>,
+    pub application_name: String,
+    pub name: String,
+    pub description: String,
+    /// The command as Discord returned it, for opening in the developer console.
+    pub definition: serde_json::Value,
+}
+
+/// Readable text autocomplete put into the composer and the markup it stands for,
+/// e.g. `@name` for `<@id>`. Only this exact span is expanded, so the same text
+/// typed by hand stays as it is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ComposerToken {
+    /// Byte offset of `display` in the composer text.
+    pub start: usize,
+    pub display: String,
+    pub markup: String,
+}
+
+impl ComposerToken {
+    fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.start + self.display.len()
+    }
+
+    /// Whether the token's text is still where it was inserted.
+    pub fn is_intact(&self, text: &str) -> bool {
+        text.get(self.range()) == Some(self.display.as_str())
+    }
+}
+
+/// Moves tokens along with an edit that turned `old` into `new`. Tokens the edit
+/// touched are dropped, so typing inside `@name` leaves plain text behind.
+pub fn shift_tokens(tokens: &mut Vec<ComposerToken>, old: &str, new: &str) {
+    if old == new {
+        return;
+    }
+    // The edit is whatever lies between the common prefix and the common suffix
+    let prefix = old.char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((index, _), _)| index)
+        .unwrap_or(old.len().min(new.len()));
+    let suffix: usize = old[prefix..].chars().rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let edit_end = old.len() - suffix;
+
+    tokens.retain_mut(|token| {
+        let range = token.range();
+        if range.end <= prefix {
+            true
+        } else if range.start >= edit_end {
+            token.start = token.start + new.len() - old.len();
+            true
+        } else {
+            false
+        }
+    });
+    tokens.retain(|token| token.is_intact(new));
+}
+
+/// Replaces each token's span in `text` with its markup, leaving everything else alone.
+pub fn expand_tokens(text: &str, tokens: &[ComposerToken]) -> String {
+    let mut tokens: Vec<_> = tokens.iter().filter(|token| token.is_intact(text)).collect();
+    tokens.sort_by_key(|token| token.start);
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut position = 0;
+    for token in tokens {
+        if token.start < position {
+            continue;
+        }
+        expanded.push_str(&text[position..token.start]);
+        expanded.push_str(&token.markup);
+        position = token.range().end;
+    }
+    expanded.push_str(&text[position..]);
+    expanded
+}
+
+/// Unsent composer state for a channel.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Draft {
+    pub text: String,
+    /// Autocomplete tokens in `text`.
+    pub tokens: Vec<ComposerToken>,
+}
+
+impl Draft {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+/// Discord shows a typing indicator for this long after TYPING_START unless it is repeated.
+pub const TYPING_EXPIRY_SECS: i64 = 10;
+
+/// Interactions must get an initial response within this many seconds.
+pub const INTERACTION_RESPONSE_SECS: i64 = 3;
+/// After the initial response the token stays valid this long for edits.
+pub const INTERACTION_TOKEN_SECS: i64 = 15 * 60;
+/// How many received interactions the inbox keeps.
+pub const INTERACTION_INBOX_LIMIT: usize = 50;
+
+/// Interaction types as numbered in payloads.
+pub const PING_INTERACTION: u64 = 1;
+pub const COMMAND_INTERACTION: u64 = 2;
+pub const COMPONENT_INTERACTION: u64 = 3;
+pub const AUTOCOMPLETE_INTERACTION: u64 = 4;
+pub const MODAL_SUBMIT_INTERACTION: u64 = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InteractionResponseState {
+    Pending,
+    /// Acknowledged with a deferred response, waiting for the real one.
+    Deferred,
+    Responded,
+}
+
+/// An interaction a bot received, kept for the interaction inbox.
+#[derive(Clone)]
+pub struct InteractionInfo {
+    pub id: String,
+    pub token: String,
+    pub application_id: String,
+    pub kind: u64,
+    /// The command name or custom id the interaction is about.
+    pub label: String,
+    pub user: String,
+    pub received_at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+    pub state: InteractionResponseState,
+}
+
+impl InteractionInfo {
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind {
+            PING_INTERACTION => "Ping",
+            COMMAND_INTERACTION => "Command",
+            COMPONENT_INTERACTION => "Component",
+            AUTOCOMPLETE_INTERACTION => "Autocomplete",
+            MODAL_SUBMIT_INTERACTION => "Modal submit",
+            _ => "Unknown",
+        }
+    }
+
+    /// When the current response window closes.
+    pub fn deadline(&self) -> DateTime<Utc> {
+        match self.state {
+            InteractionResponseState::Pending => self.received_at + chrono::Duration::seconds(INTERACTION_RESPONSE_SECS),
+            _ => self.received_at + chrono::Duration::seconds(INTERACTION_TOKEN_SECS),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TypingInfo {
+    pub name: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 pub struct AppState {
     pub current_view: View,
     pub token: Option<String>,
+    pub current_user_id: Option<twilight_model::id::Id<UserMarker>>,
+    pub is_bot: bool,
+    /// Nitro lets user accounts use emoji from other guilds.
+    pub has_nitro: bool,
     pub http_client: Option<Arc<HttpClient>>,
     pub guilds: Vec<GuildInfo>,
     pub selected_guild: Option<twilight_model::id::Id<GuildMarker>>,
     pub channels: Vec<ChannelInfo>,
+    pub dm_channels: Vec<ChannelInfo>,
     pub selected_channel: Option<twilight_model::id::Id<ChannelMarker>>,
     pub messages: Vec<MessageInfo>,
+    /// Fetches for the selected guild's channels and the selected channel's messages.
+    /// Responses for an older selection are dropped.
+    pub channel_fetch: Option<PendingFetch>,
+    pub message_fetch: Option<PendingFetch>,
+    /// Numbers fetches so a response can tell whether it's still the current one.
+    pub fetch_generation: u64,
+    /// REST poller of the current login, stopped when another login starts.
+    pub poller: Option<tokio::task::AbortHandle>,
+    /// Gateway loop of the current login, stopped when another login starts.
+    pub gateway_task: Option<tokio::task::AbortHandle>,
     pub loading: bool,
     pub error: Option<String>,
+    pub gateway: Option<MessageSender>,
+    pub connection: ConnectionHealth,
+    /// Gateway session, a new one after an old one means events were missed.
+    pub session_id: Option<String>,
+    pub roles: HashMap<twilight_model::id::Id<GuildMarker>, Vec<RoleInfo>>,
+    pub members: HashMap<twilight_model::id::Id<GuildMarker>, HashMap<twilight_model::id::Id<UserMarker>, MemberInfo>>,
+    pub presences: HashMap<twilight_model::id::Id<UserMarker>, Status>,
+    pub profiles: HashMap<twilight_model::id::Id<UserMarker>, UserProfile>,
+    /// Newest known message per channel, from channel payloads and live messages.
+    pub last_messages: HashMap<twilight_model::id::Id<ChannelMarker>, twilight_model::id::Id<MessageMarker>>,
+    /// Newest messages known before a reconnect, for channels not yet checked for gaps.
+    pub backfill_from: HashMap<twilight_model::id::Id<ChannelMarker>, twilight_model::id::Id<MessageMarker>>,
+    pub channel_guilds: HashMap<twilight_model::id::Id<ChannelMarker>, twilight_model::id::Id<GuildMarker>>,
+    pub channel_names: HashMap<twilight_model::id::Id<ChannelMarker>, String>,
+    /// Unread mentions of us per channel.
+    pub mention_counts: HashMap<twilight_model::id::Id<ChannelMarker>, u32>,
+    /// Recent mentions across all guilds, newest last.
+    pub mentions: Vec<MentionInfo>,
+    /// Message the message pane should scroll to once it is loaded.
+    pub jump_to_message: Option<twilight_model::id::Id<MessageMarker>>,
+    /// Last message we've read per channel, persisted per account.
+    pub read_states: HashMap<twilight_model::id::Id<ChannelMarker>, twilight_model::id::Id<MessageMarker>>,
+    /// Read position of the selected channel when it was opened, for the "New messages" divider.
+    pub new_messages_since: Option<twilight_model::id::Id<MessageMarker>>,
+    /// Member list loading per guild, for guilds whose list was shown.
+    pub member_pages: HashMap<twilight_model::id::Id<GuildMarker>, MemberPages>,
+    pub emojis: HashMap<twilight_model::id::Id<GuildMarker>, Vec<EmojiInfo>>,
+    /// Most recently used emoji first, persisted per account.
+    pub recent_emoji: Vec<PickedEmoji>,
+    /// Application commands usable in the current guild or DM.
+    pub commands: Vec<CommandInfo>,
+    /// The logged in bot's application, looked up at login.
+    pub application_id: Option<twilight_model::id::Id<ApplicationMarker>>,
+    pub application_name: String,
+    /// Commands shown in the developer console, kept as Discord returns them.
+    pub console_commands: Vec<serde_json::Value>,
+    /// Guild the console is showing commands for, `None` for global ones.
+    pub console_scope: Option<twilight_model::id::Id<GuildMarker>>,
+    /// Unsent messages per channel, persisted per account.
+    pub drafts: HashMap<twilight_model::id::Id<ChannelMarker>, Draft>,
+    /// Users currently typing per channel.
+    pub typing: HashMap<twilight_model::id::Id<ChannelMarker>, HashMap<twilight_model::id::Id<UserMarker>, TypingInfo>>,
+    /// Interactions received as a bot, newest first.
+    pub interactions: Vec<InteractionInfo>,
+    /// Acknowledge incoming interactions straight away so there's time to answer by hand.
+    pub auto_defer_interactions: bool,
+    pub show_member_list: bool,
+    pub settings: Settings,
+    pub notification_settings: NotificationSettings,
+    /// Status and activity the account shows to others.
+    pub presence: PresenceSettings,
+    pub gateway_config: GatewayConfig,
+    pub notifier: Notifier,
+    /// In-app toasts waiting to be shown by the window.
+    pub toasts: Arc<Mutex<Vec<Notification>>>,
+    pub window_active: bool,
+    /// Bumped whenever a background task changes state so views know to re-render.
+    pub revision: u64,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        let toasts = Arc::new(Mutex::new(Vec::new()));
+
         Self {
             current_view: View::Login,
             token: None,
+            current_user_id: None,
+            is_bot: true,
+            has_nitro: false,
             http_client: None,
             guilds: Vec::new(),
             selected_guild: None,
             channels: Vec::new(),
+            dm_channels: Vec::new(),
             selected_channel: None,
             messages: Vec::new(),
+            channel_fetch: None,
+            message_fetch: None,
+            fetch_generation: 0,
+            poller: None,
+            gateway_task: None,
             loading: false,
             error: None,
+            gateway: None,
+            connection: ConnectionHealth::default(),
+            session_id: None,
+            roles: HashMap::new(),
+            members: HashMap::new(),
+            presences: HashMap::new(),
+            profiles: HashMap::new(),
+            last_messages: HashMap::new(),
+            backfill_from: HashMap::new(),
+            channel_guilds: HashMap::new(),
+            channel_names: HashMap::new(),
+            mention_counts: HashMap::new(),
+            mentions: Vec::new(),
+            jump_to_message: None,
+            read_states: HashMap::new(),
+            new_messages_since: None,
+            member_pages: HashMap::new(),
+            emojis: HashMap::new(),
+            recent_emoji: Vec::new(),
+            commands: Vec::new(),
+            application_id: None,
+            application_name: String::new(),
+            console_commands: Vec::new(),
+            console_scope: None,
+            drafts: HashMap::new(),
+            typing: HashMap::new(),
+            interactions: Vec::new(),
+            auto_defer_interactions: false,
+            show_member_list: false,
+            settings: Settings::load(),
+            notification_settings: NotificationSettings::default(),
+            presence: PresenceSettings::default(),
+            gateway_config: GatewayConfig::default(),
+            notifier: Notifier::new(toasts.clone()),
+            toasts,
+            window_active: true,
+            revision: 0,
+        }
+    }
+
+    pub fn guild_roles(&self, guild_id: twilight_model::id::Id<GuildMarker>) -> &[RoleInfo] {
+        self.roles.get(&guild_id).map(|roles| roles.as_slice()).unwrap_or(&[])
+    }
+
+    /// Color of the highest positioned role in `roles` that has a non-default color.
+    pub fn role_color(&self, guild_id: twilight_model::id::Id<GuildMarker>, roles: &[twilight_model::id::Id<RoleMarker>]) -> Option<u32> {
+        self.guild_roles(guild_id)
+            .iter()
+            .filter(|role| role.color != 0 && roles.contains(&role.id))
+            .max_by_key(|role| role.position)
+            .map(|role| role.color)
+    }
+
+    pub fn member_color(&self, guild_id: twilight_model::id::Id<GuildMarker>, member: &MemberInfo) -> Option<u32> {
+        self.role_color(guild_id, &member.roles)
+    }
+
+    /// Fills in the author's guild display name, avatar and role color from the
+    /// member cache. Messages fetched over REST carry no member data, and the cache
+    /// may only be populated after the messages arrive.
+    pub fn resolve_author(&self, guild_id: twilight_model::id::Id<GuildMarker>, msg: &mut MessageInfo) {
+        if let Some(member) = self.members.get(&guild_id).and_then(|m| m.get(&msg.author_id)) {
+            msg.author_name = member.display_name.clone();
+            msg.author_avatar_url = member.avatar_url.clone().or(msg.author_avatar_url.take());
+            msg.author_roles = member.roles.clone();
+        }
+        msg.author_color = self.role_color(guild_id, &msg.author_roles);
+    }
+
+    /// Highest positioned hoisted role of the member, used for member list grouping.
+    pub fn member_hoisted_role(&self, guild_id: twilight_model::id::Id<GuildMarker>, member: &MemberInfo) -> Option<RoleInfo> {
+        self.guild_roles(guild_id)
+            .iter()
+            .filter(|role| role.hoist && member.roles.contains(&role.id))
+            .max_by_key(|role| role.position)
+            .cloned()
+    }
+
+    fn read_states_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("read_states_{}", id))
+    }
+
+    pub fn load_read_states(&mut self) {
+        if let Some(key) = self.read_states_key() {
+            self.read_states = Storage::load(&key);
+        }
+    }
+
+    /// Read states change with every message in the open channel, so they're written
+    /// in batches. Discord keeps no read states for bots, so they only live here.
+    fn save_read_states(&self) {
+        if let Some(key) = self.read_states_key() {
+            Storage::save_later(&key, &self.read_states);
+        }
+    }
+
+    fn drafts_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("drafts_{}", id))
+    }
+
+    pub fn load_drafts(&mut self) {
+        if let Some(key) = self.drafts_key() {
+            self.drafts = Storage::load(&key);
+        }
+    }
+
+    /// Drafts change with every keystroke, so writes are batched.
+    pub fn save_drafts(&self) {
+        if let Some(key) = self.drafts_key() {
+            Storage::save_later(&key, &self.drafts);
+        }
+    }
+
+    fn recent_emoji_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("recent_emoji_{}", id))
+    }
+
+    pub fn load_recent_emoji(&mut self) {
+        if let Some(key) = self.recent_emoji_key() {
+            self.recent_emoji = Storage::load(&key);
+        }
+    }
+
+    pub fn record_recent_emoji(&mut self, emoji: PickedEmoji) {
+        self.recent_emoji.retain(|recent| *recent != emoji);
+        self.recent_emoji.insert(0, emoji);
+        self.recent_emoji.truncate(RECENT_EMOJI_LIMIT);
+        if let Some(key) = self.recent_emoji_key() {
+            Storage::save(&key, &self.recent_emoji);
+        }
+    }
+
+    /// Guilds whose custom emoji we may use outside of them: bots and Nitro
+    /// users can use all of them, everyone else only the current guild's.
+    pub fn usable_emoji_guilds(&self) -> Vec<twilight_model::id::Id<GuildMarker>> {
+        if self.is_bot || self.has_nitro {
+            self.guilds.iter().map(|guild| guild.id).collect()
+        } else {
+            self.selected_guild.into_iter().collect()
+        }
+    }
+
+    pub fn has_draft(&self, channel_id: twilight_model::id::Id<ChannelMarker>) -> bool {
+        self.drafts.get(&channel_id).map(|draft| !draft.is_empty()).unwrap_or(false)
+    }
+
+    /// Records the newest message of a channel as seen in a channel payload.
+    /// Channels we've never tracked start out read instead of lighting up everything.
+    pub fn record_last_message(
+        &mut self,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        guild_id: Option<twilight_model::id::Id<GuildMarker>>,
+        message_id: Option<twilight_model::id::Id<MessageMarker>>,
+    ) {
+        if let Some(guild_id) = guild_id {
+            self.channel_guilds.insert(channel_id, guild_id);
+        }
+        if let Some(message_id) = message_id {
+            self.raise_last_message(channel_id, message_id);
+            self.read_states.entry(channel_id).or_insert(message_id);
+        }
+    }
+
+    /// Moves a channel's newest message forward. Backfills and replayed events can
+    /// deliver older messages after newer ones, which must not move it back.
+    pub fn raise_last_message(
+        &mut self,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        message_id: twilight_model::id::Id<MessageMarker>,
+    ) {
+        let last = self.last_messages.entry(channel_id).or_insert(message_id);
+        *last = (*last).max(message_id);
+    }
+
+    pub fn is_channel_unread(&self, channel_id: twilight_model::id::Id<ChannelMarker>) -> bool {
+        match (self.last_messages.get(&channel_id), self.read_states.get(&channel_id)) {
+            (Some(last), Some(read)) => last > read,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+
+    /// Muted guilds and channels don't light up as unread.
+    pub fn is_guild_unread(&self, guild_id: twilight_model::id::Id<GuildMarker>) -> bool {
+        !self.is_guild_muted(guild_id)
+            && self.channel_guilds
+                .iter()
+                .any(|(channel_id, id)| {
+                    *id == guild_id && !self.is_channel_muted(*channel_id) && self.is_channel_unread(*channel_id)
+                })
+    }
+
+    pub fn is_guild_muted(&self, guild_id: twilight_model::id::Id<GuildMarker>) -> bool {
+        self.notification_settings.guild(guild_id).is_muted(Utc::now())
+    }
+
+    /// Whether the channel or its guild is muted.
+    pub fn is_channel_muted(&self, channel_id: twilight_model::id::Id<ChannelMarker>) -> bool {
+        let guild_id = self.channel_guilds.get(&channel_id).copied();
+        self.notification_settings.is_muted(guild_id, channel_id, Utc::now())
+    }
+
+    fn gateway_config_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("gateway_config_{}", id))
+    }
+
+    pub fn load_gateway_config(&mut self) {
+        if let Some(key) = self.gateway_config_key() {
+            self.gateway_config = Storage::load(&key);
+        }
+    }
+
+    pub fn save_gateway_config(&self) {
+        if let Some(key) = self.gateway_config_key() {
+            Storage::save(&key, &self.gateway_config);
+        }
+    }
+
+    fn presence_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("presence_{}", id))
+    }
+
+    pub fn load_presence(&mut self) {
+        if let Some(key) = self.presence_key() {
+            self.presence = Storage::load(&key);
+        }
+    }
+
+    pub fn save_presence(&self) {
+        if let Some(key) = self.presence_key() {
+            Storage::save(&key, &self.presence);
+        }
+    }
+
+    fn notification_settings_key(&self) -> Option<String> {
+        self.current_user_id.map(|id| format!("notification_settings_{}", id))
+    }
+
+    pub fn load_notification_settings(&mut self) {
+        if let Some(key) = self.notification_settings_key() {
+            self.notification_settings = Storage::load(&key);
+        }
+    }
+
+    pub fn save_notification_settings(&self) {
+        if let Some(key) = self.notification_settings_key() {
+            Storage::save(&key, &self.notification_settings);
+        }
+    }
+
+    pub fn mark_channel_read(&mut self, channel_id: twilight_model::id::Id<ChannelMarker>) {
+        self.mention_counts.remove(&channel_id);
+        if let Some(last) = self.last_messages.get(&channel_id).copied() {
+            self.read_states.insert(channel_id, last);
+            self.save_read_states();
+        }
+    }
+
+    pub fn mark_guild_read(&mut self, guild_id: twilight_model::id::Id<GuildMarker>) {
+        let channels: Vec<_> = self.channel_guilds
+            .iter()
+            .filter(|(_, id)| **id == guild_id)
+            .map(|(channel_id, _)| *channel_id)
+            .collect();
+        for channel_id in channels {
+            self.mention_counts.remove(&channel_id);
+            if let Some(last) = self.last_messages.get(&channel_id).copied() {
+                self.read_states.insert(channel_id, last);
+            }
         }
+        self.save_read_states();
+    }
+
+    /// Whether a guild is too big to list every member. Guilds of unknown size count
+    /// as large until GUILD_CREATE reports their member count.
+    pub fn is_large_guild(&self, guild_id: twilight_model::id::Id<GuildMarker>) -> bool {
+        self.guilds.iter()
+            .find(|guild| guild.id == guild_id)
+            .and_then(|guild| guild.member_count)
+            .is_none_or(|count| count > LARGE_GUILD_MEMBERS)
+    }
+
+    /// Whether a message pings us directly, through one of our roles, or with @everyone/@here.
+    pub fn is_mentioned(
+        &self,
+        guild_id: Option<twilight_model::id::Id<GuildMarker>>,
+        mentions: &[twilight_model::id::Id<UserMarker>],
+        mention_roles: &[twilight_model::id::Id<RoleMarker>],
+        mention_everyone: bool,
+    ) -> bool {
+        let Some(user_id) = self.current_user_id else {
+            return false;
+        };
+        let role_mentioned = guild_id
+            .and_then(|guild_id| self.members.get(&guild_id))
+            .and_then(|members| members.get(&user_id))
+            .map(|me| me.roles.iter().any(|role| mention_roles.contains(role)))
+            .unwrap_or(false);
+        let guild_settings = guild_id.map(|id| self.notification_settings.guild(id)).unwrap_or_default();
+
+        guild_settings.counts_as_mention(mentions.contains(&user_id), mention_everyone, role_mentioned)
+    }
+
+    pub fn channel_mention_count(&self, channel_id: twilight_model::id::Id<ChannelMarker>) -> u32 {
+        self.mention_counts.get(&channel_id).copied().unwrap_or(0)
+    }
+
+    pub fn guild_mention_count(&self, guild_id: twilight_model::id::Id<GuildMarker>) -> u32 {
+        self.mention_counts
+            .iter()
+            .filter(|(channel_id, _)| self.channel_guilds.get(channel_id) == Some(&guild_id))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Adds a mention to the inbox and the channel's badge, once per message.
+    pub fn record_mention(&mut self, mention: MentionInfo) {
+        if self.mentions.iter().any(|existing| existing.message.id == mention.message.id) {
+            return;
+        }
+        *self.mention_counts.entry(mention.channel_id).or_default() += 1;
+        self.mentions.push(mention);
+        if self.mentions.len() > MENTIONS_INBOX_LIMIT {
+            self.mentions.remove(0);
+        }
+    }
+
+    /// Names of the people typing in a channel, not counting us.
+    pub fn typing_names(&self, channel_id: twilight_model::id::Id<ChannelMarker>) -> Vec<String> {
+        let now = Utc::now();
+        let mut typing: Vec<_> = self.typing
+            .get(&channel_id)
+            .map(|users| {
+                users.iter()
+                    .filter(|(user_id, info)| Some(**user_id) != self.current_user_id && info.expires_at > now)
+                    .map(|(_, info)| info.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        typing.sort_by_key(|info| info.expires_at);
+        typing.into_iter().map(|info| info.name).collect()
+    }
+
+    /// Drops expired typing entries, returning whether anything changed.
+    pub fn prune_typing(&mut self) -> bool {
+        let now = Utc::now();
+        let mut changed = false;
+        self.typing.retain(|_, users| {
+            let before = users.len();
+            users.retain(|_, info| info.expires_at > now);
+            changed |= users.len() != before;
+            !users.is_empty()
+        });
+        changed
+    }
+
+    pub fn is_online(&self, user_id: twilight_model::id::Id<UserMarker>) -> bool {
+        !matches!(
+            self.presences.get(&user_id),
+            None | Some(Status::Offline) | Some(Status::Invisible)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(start: usize, display: &str, markup: &str) -> ComposerToken {
+        ComposerToken { start, display: display.to_string(), markup: markup.to_string() }
+    }
+
+    #[test]
+    fn expands_only_token_spans() {
+        // Two members called Sam, and the name typed by hand
+        let text = "@Sam and @Sam, not @Sam";
+        let tokens = vec![token(0, "@Sam", "<@1>"), token(9, "@Sam", "<@2>")];
+        assert_eq!(expand_tokens(text, &tokens), "<@1> and <@2>, not @Sam");
+    }
+
+    #[test]
+    fn overlapping_names_stay_separate() {
+        let text = "@Al @Alice";
+        let tokens = vec![token(0, "@Al", "<@1>"), token(4, "@Alice", "<@2>")];
+        assert_eq!(expand_tokens(text, &tokens), "<@1> <@2>");
+    }
+
+    #[test]
+    fn edits_before_a_token_shift_it() {
+        let mut tokens = vec![token(3, "@Sam", "<@1>")];
+        shift_tokens(&mut tokens, "hi @Sam", "oh, hi @Sam");
+        assert_eq!(tokens, vec![token(7, "@Sam", "<@1>")]);
+
+        shift_tokens(&mut tokens, "oh, hi @Sam", "hi @Sam");
+        assert_eq!(tokens, vec![token(3, "@Sam", "<@1>")]);
+    }
+
+    #[test]
+    fn edits_after_a_token_keep_it() {
+        let mut tokens = vec![token(0, "@Sam", "<@1>")];
+        shift_tokens(&mut tokens, "@Sam", "@Sam hello");
+        assert_eq!(tokens, vec![token(0, "@Sam", "<@1>")]);
+    }
+
+    #[test]
+    fn edits_inside_a_token_drop_it() {
+        let mut tokens = vec![token(0, "@Sam", "<@1>"), token(5, "#general", "<#2>")];
+        shift_tokens(&mut tokens, "@Sam #general", "@Sim #general");
+        assert_eq!(tokens, vec![token(5, "#general", "<#2>")]);
+    }
+
+    #[test]
+    fn multibyte_text_shifts_by_bytes() {
+        // é is two bytes long
+        let mut tokens = vec![token(3, ":blob:", "<:blob:3>")];
+        shift_tokens(&mut tokens, "é :blob:", "ééé :blob:");
+        assert_eq!(tokens, vec![token(7, ":blob:", "<:blob:3>")]);
+        assert_eq!(expand_tokens("ééé :blob:", &tokens), "ééé <:blob:3>");
     }
 }
@@ -1,6 +1,7 @@
 mod app;
 mod views;
 mod services;
+mod settings;
 mod utils;
 
 use gpui::*;
@@ -16,6 +17,9 @@ fn main() {
 
     app.run(move |cx| {
         gpui_component::init(cx);
+        // Write out state that was waiting to be batched
+        cx.on_app_quit(|_| async { services::storage::Storage::flush() }).detach();
+        views::channel_messages::init(cx);
 
         cx.spawn(async move |cx| {
             let app_state = Arc::new(Mutex::new(AppState::new()));
@@ -0,0 +1,477 @@
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// Discord's limits for application commands, checked before anything is sent
+/// so mistakes show up in the console instead of as a 400.
+const MAX_NAME_LEN: usize = 32;
+const MAX_DESCRIPTION_LEN: usize = 100;
+const MAX_OPTIONS: usize = 25;
+const MAX_CHOICES: usize = 25;
+const MAX_CHOICE_LEN: usize = 100;
+const MAX_TEXT_LENGTH: u64 = 6000;
+/// Names, descriptions and choice values of a command together, counting the
+/// longest localization of each.
+const MAX_COMBINED_LEN: usize = 4000;
+const MAX_CHAT_INPUT_COMMANDS: usize = 100;
+const MAX_CONTEXT_MENU_COMMANDS: usize = 15;
+
+const CHAT_INPUT: u64 = 1;
+const SUB_COMMAND: u64 = 1;
+const SUB_COMMAND_GROUP: u64 = 2;
+const STRING: u64 = 3;
+const INTEGER: u64 = 4;
+const NUMBER: u64 = 10;
+
+/// Human name for a command `type`.
+pub fn command_kind(command: &Value) -> &'static str {
+    match command["type"].as_u64().unwrap_or(CHAT_INPUT) {
+        1 => "Slash",
+        2 => "User",
+        3 => "Message",
+        4 => "Entry point",
+        _ => "Unknown",
+    }
+}
+
+fn len(value: &Value) -> usize {
+    value.as_str().map(|text| text.chars().count()).unwrap_or(0)
+}
+
+/// Length of a field, or of its longest localization if that's longer.
+fn longest(value: &Value, localizations: &Value) -> usize {
+    localizations
+        .as_object()
+        .into_iter()
+        .flat_map(Map::values)
+        .map(len)
+        .fold(len(value), usize::max)
+}
+
+fn check_name(name: &Value, chat_input: bool, what: &str, errors: &mut Vec<String>) {
+    let Some(name) = name.as_str() else {
+        errors.push(format!("{} is missing a name.", what));
+        return;
+    };
+    let count = name.chars().count();
+    if count == 0 || count > MAX_NAME_LEN {
+        errors.push(format!("{} name \"{}\" must be 1-{} characters.", what, name, MAX_NAME_LEN));
+    }
+    // Context menu commands may use spaces and capitals
+    if chat_input {
+        if name.chars().any(|c| !(c.is_alphanumeric() || c == '-' || c == '_')) {
+            errors.push(format!("{} name \"{}\" may only use letters, numbers, - and _.", what, name));
+        }
+        if name.chars().any(char::is_uppercase) {
+            errors.push(format!("{} name \"{}\" must be lowercase.", what, name));
+        }
+    }
+}
+
+fn check_description(description: &Value, what: &str, errors: &mut Vec<String>) {
+    let count = len(description);
+    if count == 0 || count > MAX_DESCRIPTION_LEN {
+        errors.push(format!("{} description must be 1-{} characters.", what, MAX_DESCRIPTION_LEN));
+    }
+}
+
+fn check_localizations(field: &Value, max: usize, what: &str, errors: &mut Vec<String>) {
+    if field.is_null() {
+        return;
+    }
+    let Some(localizations) = field.as_object() else {
+        errors.push(format!("{} localizations must be an object of locale to text.", what));
+        return;
+    };
+    for (locale, text) in localizations {
+        let count = len(text);
+        if count == 0 || count > max {
+            errors.push(format!("{} localization for {} must be 1-{} characters.", what, locale, max));
+        }
+    }
+}
+
+/// Checks one level of options, returning the combined length they add.
+fn check_options(options: &Value, parent: Option<u64>, what: &str, errors: &mut Vec<String>) -> usize {
+    if options.is_null() {
+        return 0;
+    }
+    let Some(options) = options.as_array() else {
+        errors.push(format!("{} options must be an array.", what));
+        return 0;
+    };
+    if options.len() > MAX_OPTIONS {
+        errors.push(format!("{} has {} options, the limit is {}.", what, options.len(), MAX_OPTIONS));
+    }
+
+    let nested = options.iter()
+        .filter(|option| matches!(option["type"].as_u64(), Some(SUB_COMMAND | SUB_COMMAND_GROUP)))
+        .count();
+    if nested > 0 && nested < options.len() {
+        errors.push(format!("{} mixes subcommands with regular options.", what));
+    }
+
+    let mut total = 0;
+    let mut names = HashSet::new();
+    let mut seen_optional = false;
+    for option in options {
+        let name = option["name"].as_str().unwrap_or_default();
+        let label = format!("Option \"{}\" of {}", name, what.to_lowercase());
+        let kind = option["type"].as_u64();
+        check_name(&option["name"], true, &label, errors);
+        check_description(&option["description"], &label, errors);
+        check_localizations(&option["name_localizations"], MAX_NAME_LEN, &label, errors);
+        check_localizations(&option["description_localizations"], MAX_DESCRIPTION_LEN, &label, errors);
+        total += longest(&option["name"], &option["name_localizations"])
+            + longest(&option["description"], &option["description_localizations"]);
+
+        if !names.insert(name) {
+            errors.push(format!("{} has two options named \"{}\".", what, name));
+        }
+
+        match (parent, kind) {
+            (_, None) => errors.push(format!("{} is missing a type.", label)),
+            (Some(SUB_COMMAND_GROUP), Some(kind)) if kind != SUB_COMMAND => {
+                errors.push(format!("{} must be a subcommand, groups only hold subcommands.", label));
+            }
+            (Some(SUB_COMMAND), Some(SUB_COMMAND | SUB_COMMAND_GROUP)) => {
+                errors.push(format!("{} can't be nested inside a subcommand.", label));
+            }
+            _ => {}
+        }
+
+        if matches!(kind, Some(SUB_COMMAND | SUB_COMMAND_GROUP)) {
+            total += check_options(&option["options"], kind, &label, errors);
+            continue;
+        }
+
+        if option["required"].as_bool().unwrap_or(false) {
+            if seen_optional {
+                errors.push(format!("{} is required but comes after an optional option.", label));
+            }
+        } else {
+            seen_optional = true;
+        }
+
+        if let Some(choices) = option["choices"].as_array() {
+            if !matches!(kind, Some(STRING | INTEGER | NUMBER)) {
+                errors.push(format!("{} can't have choices.", label));
+            }
+            if option["autocomplete"].as_bool().unwrap_or(false) {
+                errors.push(format!("{} can't use both choices and autocomplete.", label));
+            }
+            if choices.len() > MAX_CHOICES {
+                errors.push(format!("{} has {} choices, the limit is {}.", label, choices.len(), MAX_CHOICES));
+            }
+            for choice in choices {
+                let count = len(&choice["name"]);
+                if count == 0 || count > MAX_CHOICE_LEN {
+                    errors.push(format!("Choice names of {} must be 1-{} characters.", label.to_lowercase(), MAX_CHOICE_LEN));
+                }
+                check_localizations(&choice["name_localizations"], MAX_CHOICE_LEN, &label, errors);
+                if len(&choice["value"]) > MAX_CHOICE_LEN {
+                    errors.push(format!("Choice values of {} can be at most {} characters.", label.to_lowercase(), MAX_CHOICE_LEN));
+                }
+                total += longest(&choice["name"], &choice["name_localizations"]) + len(&choice["value"]);
+            }
+        }
+
+        for bound in ["min_length", "max_length"] {
+            if let Some(length) = option[bound].as_u64() {
+                if kind != Some(STRING) {
+                    errors.push(format!("{} can only set {} on string options.", label, bound));
+                } else if length > MAX_TEXT_LENGTH {
+                    errors.push(format!("{} {} can be at most {}.", label, bound, MAX_TEXT_LENGTH));
+                }
+            }
+        }
+    }
+    total
+}
+
+/// Everything wrong with one command, empty if Discord should accept it.
+pub fn validate_command(command: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !command.is_object() {
+        errors.push("A command must be a JSON object.".to_string());
+        return errors;
+    }
+
+    let kind = command["type"].as_u64().unwrap_or(CHAT_INPUT);
+    let chat_input = kind == CHAT_INPUT;
+    check_name(&command["name"], chat_input, "Command", &mut errors);
+    check_localizations(&command["name_localizations"], MAX_NAME_LEN, "Command name", &mut errors);
+
+    if chat_input {
+        check_description(&command["description"], "Command", &mut errors);
+        check_localizations(&command["description_localizations"], MAX_DESCRIPTION_LEN, "Command description", &mut errors);
+    } else if len(&command["description"]) > 0 {
+        errors.push(format!("{} commands must have an empty description.", command_kind(command)));
+    }
+
+    if !command["default_member_permissions"].is_null()
+        && command["default_member_permissions"]
+            .as_str()
+            .is_none_or(|permissions| permissions.parse::<u64>().is_err())
+    {
+        errors.push("default_member_permissions must be a permission bitfield as a string, like \"8\".".to_string());
+    }
+
+    let mut total = longest(&command["name"], &command["name_localizations"])
+        + longest(&command["description"], &command["description_localizations"]);
+    if chat_input {
+        total += check_options(&command["options"], None, "Command", &mut errors);
+    } else if !command["options"].is_null() {
+        errors.push(format!("{} commands can't have options.", command_kind(command)));
+    }
+    if total > MAX_COMBINED_LEN {
+        errors.push(format!(
+            "Names, descriptions and values add up to {} characters, the limit is {}.",
+            total, MAX_COMBINED_LEN
+        ));
+    }
+
+    errors
+}
+
+/// Checks a whole command list, as used for imports that replace every command.
+pub fn validate_commands(commands: &[Value]) -> Vec<String> {
+    let mut errors: Vec<String> = commands
+        .iter()
+        .flat_map(|command| {
+            let name = command["name"].as_str().unwrap_or("?").to_string();
+            validate_command(command)
+                .into_iter()
+                .map(move |error| format!("/{}: {}", name, error))
+        })
+        .collect();
+
+    let mut names = HashSet::new();
+    for command in commands {
+        let key = (command["type"].as_u64().unwrap_or(CHAT_INPUT), command["name"].as_str().unwrap_or_default());
+        if !names.insert(key) {
+            errors.push(format!("There are two {} commands named \"{}\".", command_kind(command).to_lowercase(), key.1));
+        }
+    }
+
+    for (kind, limit) in [(1, MAX_CHAT_INPUT_COMMANDS), (2, MAX_CONTEXT_MENU_COMMANDS), (3, MAX_CONTEXT_MENU_COMMANDS)] {
+        let count = commands
+            .iter()
+            .filter(|command| command["type"].as_u64().unwrap_or(CHAT_INPUT) == kind)
+            .count();
+        if count > limit {
+            errors.push(format!("{} commands of type {}, the limit is {}.", count, kind, limit));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Checks that `errors` has exactly one message containing `expected`, or none.
+    fn assert_errors(case: &str, errors: &[String], expected: Option<&str>) {
+        match expected {
+            None => assert!(errors.is_empty(), "{}: unexpected errors {:?}", case, errors),
+            Some(expected) => assert!(
+                errors.len() == 1 && errors[0].contains(expected),
+                "{}: expected only \"{}\", got {:?}",
+                case, expected, errors
+            ),
+        }
+    }
+
+    fn slash(options: Value) -> Value {
+        json!({ "name": "cmd", "description": "A command", "options": options })
+    }
+
+    fn option(kind: u64, name: &str) -> Value {
+        json!({ "type": kind, "name": name, "description": "An option" })
+    }
+
+    fn required(kind: u64, name: &str) -> Value {
+        with(option(kind, name), "required", json!(true))
+    }
+
+    fn with(mut value: Value, key: &str, field: Value) -> Value {
+        value[key] = field;
+        value
+    }
+
+    fn choices(count: usize, name_len: usize, value_len: usize) -> Value {
+        (0..count)
+            .map(|_| json!({ "name": "n".repeat(name_len), "value": "v".repeat(value_len) }))
+            .collect()
+    }
+
+    #[test]
+    fn names() {
+        let (max_len, too_long) = ("a".repeat(32), "a".repeat(33));
+        let cases = [
+            ("plain", 1, "ping", None),
+            ("dashes and underscores", 1, "ping-pong_2", None),
+            ("empty", 1, "", Some("must be 1-32 characters")),
+            ("too long", 1, too_long.as_str(), Some("must be 1-32 characters")),
+            ("longest allowed", 1, max_len.as_str(), None),
+            ("uppercase", 1, "Ping", Some("must be lowercase")),
+            ("space", 1, "ping pong", Some("may only use letters")),
+            ("context menu with spaces and capitals", 2, "Show Profile", None),
+            ("context menu too long", 3, too_long.as_str(), Some("must be 1-32 characters")),
+        ];
+        for (case, kind, name, expected) in cases {
+            let command = if kind == CHAT_INPUT {
+                json!({ "name": name, "description": "A command" })
+            } else {
+                json!({ "type": kind, "name": name })
+            };
+            assert_errors(case, &validate_command(&command), expected);
+        }
+    }
+
+    #[test]
+    fn nesting() {
+        let cases = [
+            (
+                "group of subcommands",
+                json!([with(option(SUB_COMMAND_GROUP, "group"), "options", json!([option(SUB_COMMAND, "sub")]))]),
+                None,
+            ),
+            (
+                "subcommand with options",
+                json!([with(option(SUB_COMMAND, "sub"), "options", json!([option(STRING, "text")]))]),
+                None,
+            ),
+            (
+                "option directly in a group",
+                json!([with(option(SUB_COMMAND_GROUP, "group"), "options", json!([option(STRING, "text")]))]),
+                Some("groups only hold subcommands"),
+            ),
+            (
+                "subcommand in a subcommand",
+                json!([with(option(SUB_COMMAND, "sub"), "options", json!([option(SUB_COMMAND, "inner")]))]),
+                Some("can't be nested inside a subcommand"),
+            ),
+            (
+                "group in a subcommand",
+                json!([with(option(SUB_COMMAND, "sub"), "options", json!([option(SUB_COMMAND_GROUP, "inner")]))]),
+                Some("can't be nested inside a subcommand"),
+            ),
+            (
+                "subcommand next to an option",
+                json!([option(SUB_COMMAND, "sub"), option(STRING, "text")]),
+                Some("mixes subcommands with regular options"),
+            ),
+        ];
+        for (case, options, expected) in cases {
+            assert_errors(case, &validate_command(&slash(options)), expected);
+        }
+    }
+
+    #[test]
+    fn required_order() {
+        let cases = [
+            ("required then optional", json!([required(STRING, "a"), option(STRING, "b")]), None),
+            ("all required", json!([required(STRING, "a"), required(INTEGER, "b")]), None),
+            ("all optional", json!([option(STRING, "a"), option(INTEGER, "b")]), None),
+            (
+                "optional then required",
+                json!([option(STRING, "a"), required(STRING, "b")]),
+                Some("is required but comes after an optional option"),
+            ),
+            (
+                "order is per subcommand",
+                json!([
+                    with(option(SUB_COMMAND, "one"), "options", json!([option(STRING, "a")])),
+                    with(option(SUB_COMMAND, "two"), "options", json!([required(STRING, "b")])),
+                ]),
+                None,
+            ),
+        ];
+        for (case, options, expected) in cases {
+            assert_errors(case, &validate_command(&slash(options)), expected);
+        }
+    }
+
+    #[test]
+    fn choice_lists() {
+        let cases = [
+            ("string choices", with(option(STRING, "a"), "choices", choices(3, 5, 5)), None),
+            ("integer choices", with(option(INTEGER, "a"), "choices", json!([{ "name": "one", "value": 1 }])), None),
+            ("most choices allowed", with(option(STRING, "a"), "choices", choices(25, 1, 1)), None),
+            (
+                "too many choices",
+                with(option(STRING, "a"), "choices", choices(26, 1, 1)),
+                Some("has 26 choices, the limit is 25"),
+            ),
+            (
+                "choices on a boolean",
+                with(option(5, "a"), "choices", choices(1, 1, 1)),
+                Some("can't have choices"),
+            ),
+            (
+                "choices with autocomplete",
+                with(with(option(STRING, "a"), "choices", choices(1, 1, 1)), "autocomplete", json!(true)),
+                Some("can't use both choices and autocomplete"),
+            ),
+            (
+                "empty choice name",
+                with(option(STRING, "a"), "choices", choices(1, 0, 1)),
+                Some("must be 1-100 characters"),
+            ),
+            (
+                "long choice value",
+                with(option(STRING, "a"), "choices", choices(1, 1, 101)),
+                Some("can be at most 100 characters"),
+            ),
+        ];
+        for (case, option, expected) in cases {
+            assert_errors(case, &validate_command(&slash(json!([option]))), expected);
+        }
+    }
+
+    #[test]
+    fn combined_length() {
+        // 100 for the command, 100 for the option and 3800 in choices: exactly the limit
+        let command = |option_description: usize, name_localizations: Value| {
+            json!({
+                "name": "a",
+                "name_localizations": name_localizations,
+                "description": "d".repeat(99),
+                "options": [{
+                    "type": STRING,
+                    "name": "o",
+                    "description": "d".repeat(option_description),
+                    "choices": choices(19, 100, 100),
+                }],
+            })
+        };
+        let cases = [
+            ("at the limit", command(99, Value::Null), None),
+            ("one over", command(100, Value::Null), Some("add up to 4001 characters, the limit is 4000")),
+            (
+                "longest localization counts",
+                command(99, json!({ "de": "ab" })),
+                Some("add up to 4001 characters, the limit is 4000"),
+            ),
+        ];
+        for (case, command, expected) in cases {
+            assert_errors(case, &validate_command(&command), expected);
+        }
+    }
+
+    #[test]
+    fn command_counts() {
+        let user_commands = |count: usize| -> Vec<Value> {
+            (0..count).map(|i| json!({ "type": 2, "name": format!("User {}", i) })).collect()
+        };
+        let cases = [
+            ("fifteen user commands", user_commands(15), None),
+            ("sixteen user commands", user_commands(16), Some("16 commands of type 2, the limit is 15")),
+        ];
+        for (case, commands, expected) in cases {
+            assert_errors(case, &validate_commands(&commands), expected);
+        }
+    }
+}
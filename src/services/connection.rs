@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+/// Points the gateway at another server, such as the one in `examples/fake_gateway/`.
+pub const GATEWAY_URL_VAR: &str = "OXIDECORD_GATEWAY_URL";
+
+/// How long a new connection may take to say hello before it's replaced.
+pub const HELLO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest wait between two reconnect attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ConnectionStatus {
+    #[default]
+    Disconnected,
+    Connecting,
+    Identifying,
+    Resuming,
+    Connected,
+    Reconnecting { attempt: u32 },
+    /// Discord closed the session for good, e.g. for an invalid token.
+    Failed,
+}
+
+impl ConnectionStatus {
+    pub fn label(&self) -> String {
+        match self {
+            ConnectionStatus::Disconnected => "Disconnected".to_string(),
+            ConnectionStatus::Connecting => "Connecting".to_string(),
+            ConnectionStatus::Identifying => "Identifying".to_string(),
+            ConnectionStatus::Resuming => "Resuming".to_string(),
+            ConnectionStatus::Connected => "Connected".to_string(),
+            ConnectionStatus::Reconnecting { attempt: 0 } => "Reconnecting".to_string(),
+            ConnectionStatus::Reconnecting { attempt } => format!("Reconnecting (attempt {})", attempt),
+            ConnectionStatus::Failed => "Connection closed".to_string(),
+        }
+    }
+
+    pub fn color(&self) -> u32 {
+        match self {
+            ConnectionStatus::Connected => 0x23a55a,
+            ConnectionStatus::Disconnected | ConnectionStatus::Failed => 0xf23f43,
+            _ => 0xf0b232,
+        }
+    }
+}
+
+/// Gateway connection state shown next to the channel name.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionHealth {
+    pub status: ConnectionStatus,
+    /// Average round trip of recent heartbeats.
+    pub latency: Option<Duration>,
+    /// Interval of the REST polling that stands in while the gateway is down.
+    pub polling: Option<Duration>,
+}
+
+impl ConnectionHealth {
+    pub fn summary(&self) -> String {
+        match (self.status, self.latency, self.polling) {
+            (ConnectionStatus::Connected, Some(latency), _) => format!("{} ms", latency.as_millis()),
+            (ConnectionStatus::Connected, None, _) => ConnectionStatus::Connected.label(),
+            (status, _, Some(interval)) => format!("{}, polling every {}s", status.label(), interval.as_secs()),
+            (status, _, None) => status.label(),
+        }
+    }
+}
+
+/// Doubles the wait with every failed attempt, up to a minute.
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_secs(2u64.saturating_pow(attempt)).min(MAX_RECONNECT_DELAY)
+}
+
+/// How long to go without an event before the connection counts as dead. Discord
+/// acknowledges every heartbeat, so a healthy connection is never quiet for two intervals.
+pub fn stall_limit(heartbeat_interval: Duration) -> Duration {
+    heartbeat_interval * 2 + Duration::from_secs(5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_a_minute() {
+        let delays: Vec<u64> = (0..8).map(|attempt| reconnect_delay(attempt).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(reconnect_delay(u32::MAX), MAX_RECONNECT_DELAY);
+    }
+}
@@ -1,8 +1,56 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use twilight_http::Client as HttpClient;
-use twilight_model::id::marker::{GuildMarker, ChannelMarker};
-use crate::app::{AppState, View, GuildInfo, ChannelInfo, MessageInfo, AttachmentInfo};
-use crate::utils::get_runtime_handle;
+use twilight_http::error::ErrorType;
+use twilight_model::application::command::{Command, CommandType};
+use twilight_model::application::interaction::InteractionType;
+use twilight_model::channel::message::MessageFlags;
+use twilight_model::channel::{Channel, Message};
+use twilight_model::http::attachment::Attachment;
+use twilight_model::user::PremiumType;
+use twilight_http::request::channel::reaction::RequestReactionType;
+use twilight_model::id::marker::{ApplicationMarker, GuildMarker, ChannelMarker, MessageMarker, UserMarker};
+use serde_json::Value;
+use crate::app::{AppState, View, GuildInfo, ChannelInfo, CommandInfo, InteractionInfo, InteractionResponseState, MentionInfo, MessageInfo, AttachmentInfo, PickedEmoji, UserProfile};
+use crate::services::connection::ConnectionStatus;
+use crate::services::gateway::{member_info, GatewayService};
+use crate::services::rest::RawRest;
+use crate::services::scheduler::{scheduler, PendingFetch, Priority};
+use twilight_model::guild::Member;
+use crate::utils::{avatar_url, banner_url, display_name, get_runtime_handle, guild_avatar_url, to_datetime};
+
+/// Members per page when paging through a large guild, the most Discord allows.
+const MEMBER_PAGE_SIZE: u16 = 1000;
+
+/// Discord's message length limit for accounts without Nitro.
+pub const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Pages of 100 fetched when filling a gap. Longer gaps reload the channel instead.
+const BACKFILL_PAGES: usize = 5;
+
+/// How long the gateway has to be down before polling takes over, so quick reconnects don't trigger it.
+const POLL_GRACE: Duration = Duration::from_secs(15);
+/// Poll interval right after something new arrived.
+const POLL_MIN_INTERVAL: Duration = Duration::from_secs(3);
+/// Poll interval once a channel has been quiet for a while, or after hitting a rate limit.
+const POLL_MAX_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the guild and channel lists are refreshed while polling.
+const POLL_LIST_INTERVAL: Duration = Duration::from_secs(60);
+
+fn command_info(command: Command, application_name: &str) -> Option<CommandInfo> {
+    if command.kind != CommandType::ChatInput {
+        return None;
+    }
+    Some(CommandInfo {
+        id: command.id?,
+        guild_id: command.guild_id,
+        application_name: application_name.to_string(),
+        name: command.name.clone(),
+        description: command.description.clone(),
+        definition: serde_json::to_value(&command).ok()?,
+    })
+}
 
 pub struct DiscordService;
 
@@ -13,6 +61,16 @@ impl DiscordService {
             app.token = Some(token.clone());
             app.loading = true;
             app.error = None;
+            app.application_id = None;
+            app.application_name.clear();
+            // The previous account's poller and shard would keep going with the old token
+            if let Some(poller) = app.poller.take() {
+                poller.abort();
+            }
+            if let Some(gateway_task) = app.gateway_task.take() {
+                gateway_task.abort();
+            }
+            app.gateway = None;
         }
         
         let state_clone = state.clone();
@@ -42,9 +100,34 @@ impl DiscordService {
             let mut app = state_clone.lock().unwrap();
             app.http_client = Some(http_client.clone());
         }
-        
+
         handle.spawn(async move {
-            match http_client.current_user_guilds().await {
+            match scheduler().send(Priority::Normal, "GET users/@me", http_client.current_user()).await {
+                Ok(response) => match response.model().await {
+                    Ok(user) => {
+                        if let Ok(mut state) = state_clone.lock() {
+                            state.current_user_id = Some(user.id);
+                            state.is_bot = user.bot;
+                            state.has_nitro = user.premium_type.is_some_and(|premium| premium != PremiumType::None);
+                            state.load_read_states();
+                            state.load_notification_settings();
+                            state.load_drafts();
+                            state.load_recent_emoji();
+                            // The gateway config is per account, so connect once we know who this is
+                            state.load_gateway_config();
+                        }
+                        GatewayService::connect(state_clone.clone(), token);
+                        Self::start_polling(state_clone.clone());
+                        if let Err(e) = Self::application_id(&state_clone, &http_client).await {
+                            eprintln!("Error fetching application: {:?}", e);
+                        }
+                    }
+                    Err(e) => eprintln!("Error parsing current user: {:?}", e),
+                },
+                Err(e) => eprintln!("Error fetching current user: {:?}", e),
+            }
+
+            match scheduler().send(Priority::Normal, "GET users/@me/guilds", http_client.current_user_guilds()).await {
                 Ok(response) => {
                     match response.models().await {
                         Ok(guilds) => {
@@ -52,6 +135,7 @@ impl DiscordService {
                                 state.guilds = guilds.into_iter().map(|g| GuildInfo {
                                     id: g.id,
                                     name: g.name,
+                                    member_count: None,
                                 }).collect();
                                 state.current_view = View::Channel;
                                 state.loading = false;
@@ -79,12 +163,23 @@ impl DiscordService {
     }
 
     pub fn fetch_channels(state: Arc<Mutex<AppState>>, guild_id: twilight_model::id::Id<GuildMarker>) {
-        {
+        // A click on a guild whose channels are still loading doesn't send another request
+        let Some(claim) = scheduler().claim(format!("channels:{}", guild_id)) else {
+            return;
+        };
+        let generation = {
             let mut app = state.lock().unwrap();
             app.selected_guild = Some(guild_id);
             app.loading = true;
             app.error = None;
-        }
+            if let Some(previous) = app.channel_fetch.take() {
+                previous.abort();
+            }
+            app.fetch_generation += 1;
+            GatewayService::request_members(&state, &mut app);
+            app.fetch_generation
+        };
+        Self::fetch_commands(state.clone(), Some(guild_id));
 
         let state_clone = state.clone();
         let handle = get_runtime_handle();
@@ -95,27 +190,19 @@ impl DiscordService {
         };
 
         if let Some(client) = http_client {
-            handle.spawn(async move {
-                match client.guild_channels(guild_id).await {
+            // Held until the fetch is recorded, so it can't finish before it's current
+            let mut app = state.lock().unwrap();
+            let task = handle.spawn(async move {
+                let route = format!("GET guilds/{}/channels", guild_id);
+                match scheduler().send(Priority::Normal, &route, client.guild_channels(guild_id)).await {
                     Ok(response) => {
                         match response.models().await {
                             Ok(channels) => {
-                                let filtered_channels: Vec<ChannelInfo> = channels
-                                    .into_iter()
-                                    .filter_map(|ch| {
-                                        if ch.kind == twilight_model::channel::ChannelType::GuildText {
-                                            Some(ChannelInfo {
-                                                id: ch.id,
-                                                name: ch.name.unwrap_or_else(|| "Unnamed".to_string()),
-                                            })
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect();
-
                                 if let Ok(mut state) = state_clone.lock() {
-                                    state.channels = filtered_channels;
+                                    if !PendingFetch::finish(&mut state.channel_fetch, generation) {
+                                        return;
+                                    }
+                                    Self::apply_guild_channels(&mut state, guild_id, channels);
                                     state.loading = false;
                                     state.error = None;
                                 }
@@ -123,6 +210,9 @@ impl DiscordService {
                             Err(e) => {
                                 eprintln!("Error parsing channels: {:?}", e);
                                 if let Ok(mut state) = state_clone.lock() {
+                                    if !PendingFetch::finish(&mut state.channel_fetch, generation) {
+                                        return;
+                                    }
                                     state.loading = false;
                                     state.error = Some(format!("Error parsing channels: {}", e));
                                 }
@@ -132,12 +222,16 @@ impl DiscordService {
                     Err(e) => {
                         eprintln!("Error fetching channels: {:?}", e);
                         if let Ok(mut state) = state_clone.lock() {
+                            if !PendingFetch::finish(&mut state.channel_fetch, generation) {
+                                return;
+                            }
                             state.loading = false;
                             state.error = Some(format!("Error fetching channels: {}", e));
                         }
                     }
                 }
             });
+            app.channel_fetch = Some(PendingFetch::new(generation, task.abort_handle(), claim));
         } else {
             if let Ok(mut app) = state_clone.lock() {
                 app.loading = false;
@@ -146,14 +240,88 @@ impl DiscordService {
         }
     }
 
+    /// Shows a guild's text channels and records their newest messages for unread state.
+    fn apply_guild_channels(app: &mut AppState, guild_id: twilight_model::id::Id<GuildMarker>, channels: Vec<Channel>) {
+        let channels: Vec<_> = channels
+            .into_iter()
+            .filter(|ch| ch.kind == twilight_model::channel::ChannelType::GuildText)
+            .collect();
+
+        for ch in &channels {
+            app.record_last_message(ch.id, Some(guild_id), ch.last_message_id.map(|id| id.cast()));
+            if let Some(name) = &ch.name {
+                app.channel_names.insert(ch.id, name.clone());
+            }
+        }
+        app.channels = channels
+            .into_iter()
+            .map(|ch| ChannelInfo {
+                id: ch.id,
+                name: ch.name.unwrap_or_else(|| "Unnamed".to_string()),
+            })
+            .collect();
+    }
+
     pub fn fetch_messages(state: Arc<Mutex<AppState>>, channel_id: twilight_model::id::Id<ChannelMarker>) {
-        {
+        Self::load_messages(state, channel_id, None);
+    }
+
+    /// Opens a channel (and its guild) with the history around `message_id` and scrolls to it.
+    pub fn jump_to_message(
+        state: Arc<Mutex<AppState>>,
+        guild_id: Option<twilight_model::id::Id<GuildMarker>>,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        message_id: twilight_model::id::Id<MessageMarker>,
+    ) {
+        let guild_changed = state.lock()
+            .map(|app| app.selected_guild != guild_id)
+            .unwrap_or(false);
+        if guild_changed {
+            match guild_id {
+                Some(guild_id) => Self::fetch_channels(state.clone(), guild_id),
+                // A DM, so leave the guild like `open_dm` does
+                None => {
+                    if let Ok(mut app) = state.lock() {
+                        app.selected_guild = None;
+                    }
+                    Self::fetch_commands(state.clone(), None);
+                }
+            }
+        }
+
+        if let Ok(mut app) = state.lock() {
+            app.jump_to_message = Some(message_id);
+        }
+        Self::load_messages(state, channel_id, Some(message_id));
+    }
+
+    fn load_messages(
+        state: Arc<Mutex<AppState>>,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        around: Option<twilight_model::id::Id<MessageMarker>>,
+    ) {
+        // Opening the channel that is already loading doesn't send another request
+        let Some(claim) = scheduler().claim(format!("messages:{}:{:?}", channel_id, around)) else {
+            return;
+        };
+        let generation = {
             let mut app = state.lock().unwrap();
+            // Whatever the previous channel was still loading would arrive too late
+            if let Some(previous) = app.message_fetch.take() {
+                previous.abort();
+            }
+            app.fetch_generation += 1;
             app.selected_channel = Some(channel_id);
             app.messages.clear(); // Clear old messages
+            app.new_messages_since = if app.is_channel_unread(channel_id) {
+                app.read_states.get(&channel_id).copied()
+            } else {
+                None
+            };
             app.loading = true;
             app.error = None;
-        }
+            app.fetch_generation
+        };
 
         let state_clone = state.clone();
         let handle = get_runtime_handle();
@@ -164,55 +332,45 @@ impl DiscordService {
         };
 
         if let Some(client) = http_client {
-            handle.spawn(async move {
-                match client.channel_messages(channel_id).limit(50).await {
+            // Held until the fetch is recorded, so it can't finish before it's current
+            let mut app = state.lock().unwrap();
+            let task = handle.spawn(async move {
+                let route = format!("GET channels/{}/messages", channel_id);
+                let request = client.channel_messages(channel_id);
+                let result = match around {
+                    Some(message_id) => scheduler().send(Priority::Normal, &route, request.around(message_id).limit(50)).await,
+                    None => scheduler().send(Priority::Normal, &route, request.limit(50)).await,
+                };
+                match result {
                     Ok(response) => {
                         match response.models().await {
                             Ok(messages) => {
-                                                if let Ok(mut state) = state_clone.lock() {
-                                                    state.messages = messages
-                                                        .into_iter()
-                                                        .rev() // Reverse to show oldest first
-                                                        .map(|msg| {
-                                                            let avatar_url = msg.author.avatar.map(|hash| {
-                                                                format!(
-                                                                    "https://cdn.discordapp.com/avatars/{}/{}.png",
-                                                                    msg.author.id,
-                                                                    hash
-                                                                )
-                                                            });
-                                                            let attachments: Vec<AttachmentInfo> = msg.attachments
-                                                                .into_iter()
-                                                                .map(|att| AttachmentInfo {
-                                                                    url: att.url,
-                                                                    filename: att.filename,
-                                                                    content_type: att.content_type,
-                                                                    width: att.width,
-                                                                    height: att.height,
-                                                                })
-                                                                .collect();
-                                                            MessageInfo {
-                                                                id: msg.id,
-                                                                content: msg.content,
-                                                                author_name: if msg.author.discriminator > 0 {
-                                                                    format!("{}#{:04}", msg.author.name, msg.author.discriminator)
-                                                                } else {
-                                                                    msg.author.name.clone()
-                                                                },
-                                                                author_id: msg.author.id,
-                                                                author_avatar_url: avatar_url,
-                                                                timestamp: format!("{}", msg.timestamp.as_secs()),
-                                                                attachments,
-                                                            }
-                                                        })
-                                                        .collect();
-                                                    state.loading = false;
-                                                    state.error = None;
-                                                }
-                                            }
+                                if let Ok(mut state) = state_clone.lock() {
+                                    if !PendingFetch::finish(&mut state.message_fetch, generation) {
+                                        return;
+                                    }
+                                    let guild_id = state.selected_guild;
+                                    let messages: Vec<MessageInfo> = messages
+                                        .into_iter()
+                                        .rev() // Reverse to show oldest first
+                                        .map(|msg| Self::message_info(msg, guild_id, &state))
+                                        .collect();
+                                    state.messages = messages;
+                                    if around.is_none() {
+                                        let newest = state.messages.last().map(|msg| msg.id);
+                                        state.record_last_message(channel_id, None, newest);
+                                    }
+                                    state.mark_channel_read(channel_id);
+                                    state.loading = false;
+                                    state.error = None;
+                                }
+                            }
                             Err(e) => {
                                 eprintln!("Error parsing messages: {:?}", e);
                                 if let Ok(mut state) = state_clone.lock() {
+                                    if !PendingFetch::finish(&mut state.message_fetch, generation) {
+                                        return;
+                                    }
                                     state.loading = false;
                                     state.error = Some(format!("Error parsing messages: {}", e));
                                 }
@@ -222,12 +380,16 @@ impl DiscordService {
                     Err(e) => {
                         eprintln!("Error fetching messages: {:?}", e);
                         if let Ok(mut state) = state_clone.lock() {
+                            if !PendingFetch::finish(&mut state.message_fetch, generation) {
+                                return;
+                            }
                             state.loading = false;
                             state.error = Some(format!("Error fetching messages: {}", e));
                         }
                     }
                 }
             });
+            app.message_fetch = Some(PendingFetch::new(generation, task.abort_handle(), claim));
         } else {
             if let Ok(mut app) = state_clone.lock() {
                 app.loading = false;
@@ -235,5 +397,746 @@ impl DiscordService {
             }
         }
     }
+
+    /// Fetches what a channel received after `after` while the gateway was away and
+    /// merges it in. Locks the state in the task, the gateway calls this while holding it.
+    pub fn backfill_messages(
+        state: Arc<Mutex<AppState>>,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        after: twilight_model::id::Id<MessageMarker>,
+    ) {
+        let handle = get_runtime_handle();
+
+        handle.spawn(async move {
+            let Some(client) = state.lock().ok().and_then(|app| app.http_client.clone()) else {
+                return;
+            };
+            if let Err(e) = Self::merge_messages_after(&state, &client, channel_id, after).await {
+                eprintln!("Error fetching missed messages: {:?}", e);
+                if let Ok(mut app) = state.lock() {
+                    app.error = Some(format!("Error fetching missed messages: {}", e));
+                }
+            }
+        });
+    }
+
+    /// Merges the messages after `after` into the open channel's messages, unread
+    /// state and mentions, deduplicated by id. Returns how many there were.
+    async fn merge_messages_after(
+        state: &Arc<Mutex<AppState>>,
+        client: &HttpClient,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        after: twilight_model::id::Id<MessageMarker>,
+    ) -> anyhow::Result<usize> {
+        let route = format!("GET channels/{}/messages", channel_id);
+        let mut missed = Vec::new();
+        let mut newest = after;
+        let mut complete = false;
+        for _ in 0..BACKFILL_PAGES {
+            let request = client.channel_messages(channel_id).after(newest).limit(100);
+            let page = scheduler().send(Priority::Prefetch, &route, request).await?.models().await?;
+            complete = page.len() < 100;
+            newest = page.iter().map(|msg| msg.id).max().unwrap_or(newest);
+            missed.extend(page);
+            if complete {
+                break;
+            }
+        }
+
+        let count = missed.len();
+        if count == 0 {
+            return Ok(0);
+        }
+        let Ok(mut app) = state.lock() else {
+            return Ok(count);
+        };
+        let is_open = app.selected_channel == Some(channel_id);
+        if is_open && !complete {
+            // Too much to stitch together, start over from the newest messages
+            drop(app);
+            Self::fetch_messages(state.clone(), channel_id);
+            return Ok(count);
+        }
+
+        let guild_id = app.channel_guilds.get(&channel_id).copied();
+        let read_before = app.read_states.get(&channel_id).copied();
+        missed.sort_by_key(|msg| msg.id);
+        for msg in missed {
+            let info = Self::message_info(msg, guild_id, &app);
+            let is_unread = read_before.is_none_or(|read| info.id > read);
+            let is_own = app.current_user_id == Some(info.author_id);
+            if info.mentions_me && is_unread && !is_own {
+                app.record_mention(MentionInfo {
+                    guild_id,
+                    channel_id,
+                    message: info.clone(),
+                });
+            }
+            if is_open && !app.messages.iter().any(|existing| existing.id == info.id) {
+                app.messages.push(info);
+            }
+        }
+
+        app.raise_last_message(channel_id, newest);
+        if is_open {
+            app.messages.sort_by_key(|msg| msg.id);
+            if app.new_messages_since.is_none() && app.is_channel_unread(channel_id) {
+                app.new_messages_since = read_before;
+            }
+            app.mark_channel_read(channel_id);
+        }
+        app.revision += 1;
+        Ok(count)
+    }
+
+    /// Keeps the client live over REST while the gateway is unavailable, e.g. on
+    /// networks that block WebSockets. The selected channel is polled with an `after`
+    /// cursor, quickly while messages come in and slower as it goes quiet. Requests go
+    /// one at a time through twilight's ratelimiter, which holds them back when a bucket
+    /// runs out; a slow round trip or a 429 stretches the interval.
+    fn start_polling(state: Arc<Mutex<AppState>>) {
+        let handle = get_runtime_handle();
+
+        let poller = handle.spawn({
+            let state = state.clone();
+            async move {
+                let mut interval = POLL_MIN_INTERVAL;
+                let mut down_since: Option<Instant> = None;
+                let mut lists_refreshed_at: Option<Instant> = None;
+
+                loop {
+                    tokio::time::sleep(interval).await;
+
+                    let Some((client, connected, cursor)) = state.lock().ok().and_then(|app| {
+                        let cursor = app.selected_channel.and_then(|channel_id| {
+                            app.messages.iter()
+                                .map(|msg| msg.id)
+                                .max()
+                                .or_else(|| app.last_messages.get(&channel_id).copied())
+                                .map(|newest| (channel_id, newest))
+                        });
+                        Some((app.http_client.clone()?, app.connection.status == ConnectionStatus::Connected, cursor))
+                    }) else {
+                        continue;
+                    };
+
+                    if connected {
+                        if down_since.take().is_some() {
+                            set_polling(&state, None);
+                        }
+                        interval = POLL_MIN_INTERVAL;
+                        continue;
+                    }
+                    if down_since.get_or_insert_with(Instant::now).elapsed() < POLL_GRACE {
+                        continue;
+                    }
+
+                    let started = Instant::now();
+                    let result = match cursor {
+                        Some((channel_id, after)) => Self::merge_messages_after(&state, &client, channel_id, after).await,
+                        None => Ok(0),
+                    };
+                    interval = match result {
+                        Ok(0) => (interval * 3 / 2).min(POLL_MAX_INTERVAL),
+                        Ok(_) => POLL_MIN_INTERVAL,
+                        Err(e) => {
+                            eprintln!("Error polling messages: {:?}", e);
+                            if is_rate_limited(&e) { POLL_MAX_INTERVAL } else { (interval * 2).min(POLL_MAX_INTERVAL) }
+                        }
+                    };
+                    if started.elapsed() > interval {
+                        interval = (interval * 2).min(POLL_MAX_INTERVAL);
+                    }
+
+                    if lists_refreshed_at.is_none_or(|at| at.elapsed() >= POLL_LIST_INTERVAL) {
+                        lists_refreshed_at = Some(Instant::now());
+                        if let Err(e) = Self::refresh_lists(&state, &client).await {
+                            eprintln!("Error refreshing guilds and channels: {:?}", e);
+                        }
+                    }
+                    set_polling(&state, Some(interval));
+                }
+            }
+        });
+        if let Ok(mut app) = state.lock()
+            && let Some(previous) = app.poller.replace(poller.abort_handle())
+        {
+            previous.abort();
+        }
+    }
+
+    /// Refreshes the guild list and the selected guild's channels without a loading state.
+    async fn refresh_lists(state: &Arc<Mutex<AppState>>, client: &HttpClient) -> anyhow::Result<()> {
+        let guilds = scheduler().send(Priority::Prefetch, "GET users/@me/guilds", client.current_user_guilds()).await?.models().await?;
+        let guild_id = state.lock().ok().and_then(|app| app.selected_guild);
+        let channels = match guild_id {
+            Some(guild_id) => {
+                let route = format!("GET guilds/{}/channels", guild_id);
+                Some(scheduler().send(Priority::Prefetch, &route, client.guild_channels(guild_id)).await?.models().await?)
+            }
+            None => None,
+        };
+
+        if let Ok(mut app) = state.lock() {
+            let member_counts: HashMap<_, _> = app.guilds.iter().map(|g| (g.id, g.member_count)).collect();
+            app.guilds = guilds.into_iter().map(|g| GuildInfo {
+                id: g.id,
+                name: g.name,
+                member_count: member_counts.get(&g.id).copied().flatten(),
+            }).collect();
+            // The selection may have moved on while the request was out
+            if let (Some(guild_id), Some(channels)) = (guild_id, channels)
+                && app.selected_guild == Some(guild_id)
+            {
+                Self::apply_guild_channels(&mut app, guild_id, channels);
+            }
+            app.revision += 1;
+        }
+        Ok(())
+    }
+
+    pub fn message_info(msg: Message, guild_id: Option<twilight_model::id::Id<GuildMarker>>, app: &AppState) -> MessageInfo {
+        let mentioned_users: Vec<_> = msg.mentions.iter().map(|mention| mention.id).collect();
+        let mentions_me = app.is_mentioned(guild_id, &mentioned_users, &msg.mention_roles, msg.mention_everyone);
+        let member = msg.member.as_ref();
+        let author_avatar_url = member
+            .and_then(|m| m.avatar)
+            .zip(guild_id)
+            .map(|(hash, guild_id)| guild_avatar_url(guild_id, msg.author.id, hash))
+            .or_else(|| msg.author.avatar.map(|hash| avatar_url(msg.author.id, hash)));
+        let attachments: Vec<AttachmentInfo> = msg.attachments
+            .into_iter()
+            .map(|att| AttachmentInfo {
+                url: att.url,
+                filename: att.filename,
+                content_type: att.content_type,
+                width: att.width,
+                height: att.height,
+            })
+            .collect();
+
+        MessageInfo {
+            id: msg.id,
+            content: msg.content,
+            author_name: display_name(member.and_then(|m| m.nick.as_ref()), &msg.author),
+            author_id: msg.author.id,
+            author_avatar_url,
+            author_roles: member.map(|m| m.roles.clone()).unwrap_or_default(),
+            author_color: None,
+            timestamp: to_datetime(msg.timestamp),
+            attachments,
+            mentions_me,
+            ephemeral: msg.flags.is_some_and(|flags| flags.contains(MessageFlags::EPHEMERAL)),
+            interaction: msg.interaction_metadata.map(|interaction| {
+                // Discord no longer says which command was used
+                let action = match interaction.kind {
+                    InteractionType::ApplicationCommand => "used a command",
+                    InteractionType::ModalSubmit => "submitted a form",
+                    _ => "used an app",
+                };
+                format!("{} {}", interaction.user.name, action)
+            }),
+            components: msg.components,
+        }
+    }
+
+    /// Loads the next page of a large guild's members, after the highest user id
+    /// loaded so far. The member list asks for more as it's scrolled.
+    pub fn fetch_member_page(state: &Arc<Mutex<AppState>>, app: &mut AppState, guild_id: twilight_model::id::Id<GuildMarker>) {
+        let Some(client) = app.http_client.clone() else {
+            return;
+        };
+        let pages = app.member_pages.entry(guild_id).or_default();
+        if pages.loading || pages.complete {
+            return;
+        }
+        pages.loading = true;
+        let after = pages.after;
+
+        let state = state.clone();
+        get_runtime_handle().spawn(async move {
+            let result: anyhow::Result<Vec<Member>> = async {
+                let mut request = client.guild_members(guild_id).limit(MEMBER_PAGE_SIZE);
+                if let Some(after) = after {
+                    request = request.after(after);
+                }
+                let route = format!("GET guilds/{}/members", guild_id);
+                Ok(scheduler().send(Priority::Normal, &route, request).await?.models().await?)
+            }
+            .await;
+
+            let Ok(mut app) = state.lock() else {
+                return;
+            };
+            let pages = app.member_pages.entry(guild_id).or_default();
+            pages.loading = false;
+            match result {
+                Ok(page) => {
+                    pages.complete = page.len() < usize::from(MEMBER_PAGE_SIZE);
+                    pages.after = page.iter().map(|member| member.user.id).max().or(pages.after);
+                    let members = app.members.entry(guild_id).or_default();
+                    for member in &page {
+                        members.insert(member.user.id, member_info(guild_id, member));
+                    }
+                    app.revision += 1;
+                }
+                Err(e) => eprintln!("Error fetching guild members: {:?}", e),
+            }
+        });
+    }
+
+    pub fn fetch_user_profile(state: Arc<Mutex<AppState>>, user_id: twilight_model::id::Id<UserMarker>) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            if app.profiles.contains_key(&user_id) {
+                return;
+            }
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            match scheduler().send(Priority::Normal, "GET users/:id", client.user(user_id)).await {
+                Ok(response) => match response.model().await {
+                    Ok(user) => {
+                        if let Ok(mut state) = state.lock() {
+                            state.profiles.insert(user.id, UserProfile {
+                                username: user.name.clone(),
+                                global_name: user.global_name.clone(),
+                                avatar_url: user.avatar.map(|hash| avatar_url(user.id, hash)),
+                                banner_url: user.banner.map(|hash| banner_url(user.id, hash)),
+                                accent_color: user.accent_color,
+                                bot: user.bot,
+                                // Not exposed to bot accounts
+                                pronouns: None,
+                                bio: None,
+                            });
+                            state.revision += 1;
+                        }
+                    }
+                    Err(e) => eprintln!("Error parsing user: {:?}", e),
+                },
+                Err(e) => eprintln!("Error fetching user: {:?}", e),
+            }
+        });
+    }
+
+    /// Opens (or creates) the DM channel with a user and selects it.
+    pub fn open_dm(state: Arc<Mutex<AppState>>, user_id: twilight_model::id::Id<UserMarker>, name: String) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            match scheduler().send(Priority::User, "POST users/@me/channels", client.create_private_channel(user_id)).await {
+                Ok(response) => match response.model().await {
+                    Ok(channel) => {
+                        if let Ok(mut app) = state.lock() {
+                            if !app.dm_channels.iter().any(|ch| ch.id == channel.id) {
+                                app.dm_channels.push(ChannelInfo { id: channel.id, name });
+                            }
+                            app.selected_guild = None;
+                        }
+                        Self::fetch_commands(state.clone(), None);
+                        Self::fetch_messages(state.clone(), channel.id);
+                    }
+                    Err(e) => eprintln!("Error parsing DM channel: {:?}", e),
+                },
+                Err(e) => {
+                    eprintln!("Error opening DM: {:?}", e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Error opening DM: {}", e));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Tells Discord we're typing in a channel. Callers throttle this.
+    pub fn trigger_typing(state: Arc<Mutex<AppState>>, channel_id: twilight_model::id::Id<ChannelMarker>) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let route = format!("POST channels/{}/typing", channel_id);
+            if let Err(e) = scheduler().send(Priority::Normal, &route, client.create_typing_trigger(channel_id)).await {
+                eprintln!("Error sending typing trigger: {:?}", e);
+            }
+        });
+    }
+
+    /// Sends each entry as its own message, in order.
+    pub fn send_messages(state: Arc<Mutex<AppState>>, channel_id: twilight_model::id::Id<ChannelMarker>, contents: Vec<String>) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let route = format!("POST channels/{}/messages", channel_id);
+            for content in contents {
+                if let Err(e) = scheduler().send(Priority::User, &route, client.create_message(channel_id).content(&content)).await {
+                    eprintln!("Failed to send message: {}", e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Failed to send message: {}", e));
+                    }
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Sends text that is too long for a message as a `message.txt` attachment.
+    pub fn send_as_file(state: Arc<Mutex<AppState>>, channel_id: twilight_model::id::Id<ChannelMarker>, text: String) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let attachments = [Attachment::from_bytes("message.txt".to_string(), text.into_bytes(), 0)];
+            let route = format!("POST channels/{}/messages", channel_id);
+            if let Err(e) = scheduler().send(Priority::User, &route, client.create_message(channel_id).attachments(&attachments)).await {
+                eprintln!("Failed to upload message: {}", e);
+                if let Ok(mut app) = state.lock() {
+                    app.error = Some(format!("Failed to upload message: {}", e));
+                }
+            }
+        });
+    }
+
+    pub fn add_reaction(
+        state: Arc<Mutex<AppState>>,
+        channel_id: twilight_model::id::Id<ChannelMarker>,
+        message_id: twilight_model::id::Id<MessageMarker>,
+        emoji: PickedEmoji,
+    ) {
+        let http_client = {
+            let app = state.lock().unwrap();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let reaction = match &emoji {
+                PickedEmoji::Unicode(name) => RequestReactionType::Unicode { name: name.as_str() },
+                PickedEmoji::Custom { id, name, .. } => RequestReactionType::Custom { id: *id, name: Some(name.as_str()) },
+            };
+            let route = format!("PUT channels/{}/messages/:id/reactions", channel_id);
+            if let Err(e) = scheduler().send(Priority::User, &route, client.create_reaction(channel_id, message_id, &reaction)).await {
+                eprintln!("Error adding reaction: {:?}", e);
+                if let Ok(mut app) = state.lock() {
+                    app.error = Some(format!("Error adding reaction: {}", e));
+                }
+            }
+        });
+    }
+
+    /// Loads the bot's own slash commands: the global ones plus those registered in
+    /// `guild_id`. Bots can't invoke commands, so the `/` palette only lists them.
+    pub fn fetch_commands(state: Arc<Mutex<AppState>>, guild_id: Option<twilight_model::id::Id<GuildMarker>>) {
+        let http_client = {
+            let mut app = state.lock().unwrap();
+            app.commands.clear();
+            app.http_client.clone()
+        };
+
+        let Some(client) = http_client else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let result: anyhow::Result<Vec<CommandInfo>> = async {
+                let application_id = Self::application_id(&state, &client).await?;
+                let application_name = state.lock().map(|app| app.application_name.clone()).unwrap_or_default();
+                let interaction = client.interaction(application_id);
+                let route = format!("GET applications/{}/commands", application_id);
+                let mut commands = scheduler().send(Priority::Normal, &route, interaction.global_commands()).await?.models().await?;
+                if let Some(guild_id) = guild_id {
+                    let route = format!("GET applications/{}/guilds/{}/commands", application_id, guild_id);
+                    commands.extend(scheduler().send(Priority::Normal, &route, interaction.guild_commands(guild_id)).await?.models().await?);
+                }
+                Ok(commands
+                    .into_iter()
+                    .filter_map(|command| command_info(command, &application_name))
+                    .collect())
+            }
+            .await;
+
+            match result {
+                Ok(commands) => {
+                    if let Ok(mut app) = state.lock() {
+                        // Drop the result if the user moved on while it loaded
+                        if app.selected_guild == guild_id {
+                            app.commands = commands;
+                            app.revision += 1;
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error fetching application commands: {:?}", e),
+            }
+        });
+    }
+
+    /// The bot's application id, looked up at login and cached with its name.
+    async fn application_id(state: &Arc<Mutex<AppState>>, client: &HttpClient) -> anyhow::Result<twilight_model::id::Id<ApplicationMarker>> {
+        if let Some(id) = state.lock().ok().and_then(|app| app.application_id) {
+            return Ok(id);
+        }
+        let application = scheduler()
+            .send(Priority::Normal, "GET applications/@me", client.current_user_application())
+            .await?
+            .model()
+            .await?;
+        if let Ok(mut app) = state.lock() {
+            app.application_id = Some(application.id);
+            app.application_name = application.name;
+        }
+        Ok(application.id)
+    }
+
+    fn commands_path(application_id: twilight_model::id::Id<ApplicationMarker>, scope: Option<twilight_model::id::Id<GuildMarker>>) -> String {
+        match scope {
+            Some(guild_id) => format!("/applications/{}/guilds/{}/commands", application_id, guild_id),
+            None => format!("/applications/{}/commands", application_id),
+        }
+    }
+
+    /// Client and token for console requests, which only bots can make.
+    fn console_credentials(state: &Arc<Mutex<AppState>>) -> Option<(Arc<HttpClient>, String)> {
+        let mut app = state.lock().ok()?;
+        if !app.is_bot {
+            app.error = Some("The developer console needs a bot token.".to_string());
+            return None;
+        }
+        app.http_client.clone().zip(app.token.clone())
+    }
+
+    /// Loads the commands registered globally or in one guild, with their localizations.
+    pub fn fetch_console_commands(state: Arc<Mutex<AppState>>, scope: Option<twilight_model::id::Id<GuildMarker>>) {
+        let Some((client, token)) = Self::console_credentials(&state) else {
+            return;
+        };
+        if let Ok(mut app) = state.lock() {
+            app.console_scope = scope;
+        }
+
+        get_runtime_handle().spawn(async move {
+            let result: anyhow::Result<Vec<Value>> = async {
+                let application_id = Self::application_id(&state, &client).await?;
+                let path = Self::commands_path(application_id, scope);
+                let route = format!("GET {}", path.trim_start_matches('/'));
+                RawRest::get(&token, Priority::Normal, &route, &format!("{}?with_localizations=true", path)).await
+            }
+            .await;
+
+            match result {
+                Ok(commands) => {
+                    if let Ok(mut app) = state.lock()
+                        && app.console_scope == scope
+                    {
+                        app.console_commands = commands;
+                        app.revision += 1;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error fetching console commands: {:?}", e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Couldn't load commands: {}", e));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Creates the command, or edits it if it already has an id.
+    pub fn save_console_command(state: Arc<Mutex<AppState>>, scope: Option<twilight_model::id::Id<GuildMarker>>, mut command: Value) {
+        let Some((client, token)) = Self::console_credentials(&state) else {
+            return;
+        };
+        let command_id = command["id"].as_str().map(str::to_string);
+        if let Some(fields) = command.as_object_mut() {
+            for read_only in ["id", "application_id", "guild_id", "version"] {
+                fields.remove(read_only);
+            }
+        }
+
+        get_runtime_handle().spawn(async move {
+            let result = async {
+                let path = Self::commands_path(Self::application_id(&state, &client).await?, scope);
+                match &command_id {
+                    Some(id) => {
+                        let path = format!("{}/{}", path, id);
+                        let route = format!("PATCH {}", path.trim_start_matches('/'));
+                        RawRest::patch_json(&token, Priority::User, &route, &path, &command).await
+                    }
+                    None => {
+                        let route = format!("POST {}", path.trim_start_matches('/'));
+                        RawRest::post_json(&token, Priority::User, &route, &path, &command).await
+                    }
+                }
+            }
+            .await;
+
+            match result {
+                Ok(()) => Self::fetch_console_commands(state, scope),
+                Err(e) => {
+                    eprintln!("Error saving command: {:?}", e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Couldn't save command: {}", e));
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn delete_console_command(state: Arc<Mutex<AppState>>, scope: Option<twilight_model::id::Id<GuildMarker>>, command_id: String) {
+        let Some((client, token)) = Self::console_credentials(&state) else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let result = async {
+                let path = format!("{}/{}", Self::commands_path(Self::application_id(&state, &client).await?, scope), command_id);
+                let route = format!("DELETE {}", path.trim_start_matches('/'));
+                RawRest::delete(&token, Priority::User, &route, &path).await
+            }
+            .await;
+
+            match result {
+                Ok(()) => Self::fetch_console_commands(state, scope),
+                Err(e) => {
+                    eprintln!("Error deleting command {}: {:?}", command_id, e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Couldn't delete command: {}", e));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Replaces every command in the scope with `commands`, as imports do.
+    pub fn overwrite_console_commands(state: Arc<Mutex<AppState>>, scope: Option<twilight_model::id::Id<GuildMarker>>, commands: Vec<Value>) {
+        let Some((client, token)) = Self::console_credentials(&state) else {
+            return;
+        };
+
+        get_runtime_handle().spawn(async move {
+            let result = async {
+                let path = Self::commands_path(Self::application_id(&state, &client).await?, scope);
+                let route = format!("PUT {}", path.trim_start_matches('/'));
+                RawRest::put_json(&token, Priority::User, &route, &path, &Value::Array(commands)).await
+            }
+            .await;
+
+            match result {
+                Ok(()) => Self::fetch_console_commands(state, scope),
+                Err(e) => {
+                    eprintln!("Error importing commands: {:?}", e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Couldn't import commands: {}", e));
+                    }
+                }
+            }
+        });
+    }
+
+    fn set_interaction_state(state: &Arc<Mutex<AppState>>, interaction_id: &str, response_state: InteractionResponseState) {
+        if let Ok(mut app) = state.lock()
+            && let Some(interaction) = app.interactions.iter_mut().find(|interaction| interaction.id == interaction_id)
+        {
+            interaction.state = response_state;
+            app.revision += 1;
+        }
+    }
+
+    /// Sends the initial response to an interaction the bot received. Deferred
+    /// responses (types 5 and 6) leave it open for an edit later. The interaction's
+    /// state changes right away, so nothing sends a second initial response while this
+    /// one is on its way, and goes back to pending if the request fails.
+    pub fn respond_to_interaction(state: Arc<Mutex<AppState>>, interaction: &mut InteractionInfo, response: Value) {
+        let path = format!("/interactions/{}/{}/callback", interaction.id, interaction.token);
+        // Routes show up in the debug panel, so they leave the token out
+        let route = format!("POST interactions/{}/:token/callback", interaction.id);
+        let interaction_id = interaction.id.clone();
+        interaction.state = match response["type"].as_u64() {
+            Some(5 | 6) => InteractionResponseState::Deferred,
+            _ => InteractionResponseState::Responded,
+        };
+
+        // Callers hold the state, so look the token up in the task
+        get_runtime_handle().spawn(async move {
+            let Some(token) = state.lock().ok().and_then(|app| app.token.clone()) else {
+                return;
+            };
+            if let Err(e) = RawRest::post_json(&token, Priority::User, &route, &path, &response).await {
+                eprintln!("Error responding to interaction {}: {:?}", interaction_id, e);
+                Self::set_interaction_state(&state, &interaction_id, InteractionResponseState::Pending);
+                if let Ok(mut app) = state.lock() {
+                    app.error = Some(format!("Interaction response failed: {}", e));
+                }
+            }
+        });
+    }
+
+    /// Replaces the original response, which is how a deferred interaction gets its answer.
+    pub fn edit_interaction_response(state: Arc<Mutex<AppState>>, interaction: &InteractionInfo, message: Value) {
+        let path = format!("/webhooks/{}/{}/messages/@original", interaction.application_id, interaction.token);
+        let route = format!("PATCH webhooks/{}/:token/messages/@original", interaction.application_id);
+        let interaction_id = interaction.id.clone();
+
+        get_runtime_handle().spawn(async move {
+            let Some(token) = state.lock().ok().and_then(|app| app.token.clone()) else {
+                return;
+            };
+            match RawRest::patch_json(&token, Priority::User, &route, &path, &message).await {
+                Ok(()) => Self::set_interaction_state(&state, &interaction_id, InteractionResponseState::Responded),
+                Err(e) => {
+                    eprintln!("Error editing response to interaction {}: {:?}", interaction_id, e);
+                    if let Ok(mut app) = state.lock() {
+                        app.error = Some(format!("Couldn't edit the response: {}", e));
+                    }
+                }
+            }
+        });
+    }
 }
 
+fn set_polling(state: &Arc<Mutex<AppState>>, interval: Option<Duration>) {
+    if let Ok(mut app) = state.lock()
+        && app.connection.polling != interval
+    {
+        app.connection.polling = interval;
+        app.revision += 1;
+    }
+}
+
+fn is_rate_limited(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<twilight_http::Error>()
+        .is_some_and(|e| matches!(e.kind(), ErrorType::Response { status, .. } if status.get() == 429))
+}
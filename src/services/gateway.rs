@@ -0,0 +1,724 @@
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt as _;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use twilight_gateway::error::ReceiveMessageError;
+use twilight_gateway::{ConfigBuilder, Event, EventTypeFlags, Intents, Message, Shard, ShardId, ShardState};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::gateway::payload::outgoing::RequestGuildMembers;
+use twilight_model::gateway::presence::Presence;
+use twilight_model::guild::{Emoji, Member, Role};
+use twilight_model::id::marker::{ChannelMarker, GuildMarker};
+use twilight_model::id::Id;
+use crate::app::{
+    AppState, EmojiInfo, InteractionInfo, InteractionResponseState, MemberInfo, MemberPages, MentionInfo, MessageInfo, RoleInfo,
+    TypingInfo, COMMAND_INTERACTION, COMPONENT_INTERACTION, INTERACTION_INBOX_LIMIT, MODAL_SUBMIT_INTERACTION, TYPING_EXPIRY_SECS,
+};
+use crate::services::connection::{reconnect_delay, stall_limit, ConnectionStatus, GATEWAY_URL_VAR, HELLO_TIMEOUT};
+use crate::services::discord::DiscordService;
+use crate::services::gateway_config::{close_reason, GatewayConfig};
+use crate::services::notifications::{should_notify, MessageCandidate, Notification, NotifyContext};
+use crate::utils::{avatar_url, display_name, get_runtime_handle, guild_avatar_url, to_datetime};
+
+/// Guilds with more members than this are loaded in pages instead of all at once.
+pub const LARGE_GUILD_MEMBERS: u64 = 1000;
+/// Most members a member search returns.
+const MEMBER_SEARCH_LIMIT: u64 = 100;
+
+#[cfg(test)]
+#[path = "../../examples/fake_gateway/server.rs"]
+mod fake_server;
+
+pub struct GatewayService;
+
+enum ShardExit {
+    /// Discord closed the session for good.
+    Closed,
+    /// Nothing arrived for too long, not even heartbeat ACKs.
+    Stalled { connected: bool },
+}
+
+impl GatewayService {
+    /// Keeps a gateway session alive for the logged in account. twilight heartbeats
+    /// and reconnects on its own; on top of that a shard that stops answering is
+    /// replaced, resuming its session, with exponential backoff between attempts.
+    pub fn connect(state: Arc<Mutex<AppState>>, token: String) {
+        let handle = get_runtime_handle();
+
+        let gateway_url = std::env::var(GATEWAY_URL_VAR).ok();
+        let task = handle.spawn(Self::run(state.clone(), token, gateway_url));
+        if let Ok(mut app) = state.lock()
+            && let Some(previous) = app.gateway_task.replace(task.abort_handle())
+        {
+            previous.abort();
+        }
+    }
+
+    /// Runs one shard after another until Discord closes the session for good.
+    async fn run(state: Arc<Mutex<AppState>>, token: String, gateway_url: Option<String>) {
+        let config = state.lock()
+            .map(|app| app.gateway_config.clone())
+            .unwrap_or_default();
+        let mut session = None;
+        let mut attempt = 0;
+
+        loop {
+            let mut builder = ConfigBuilder::new(token.clone(), config.intents())
+                .large_threshold(config.large_threshold());
+            if let Some(url) = &gateway_url {
+                builder = builder.proxy_url(url.clone());
+            }
+            let resuming = session.is_some();
+            if let Some(session) = session.take() {
+                builder = builder.session(session);
+            }
+            set_status(&state, if resuming { ConnectionStatus::Resuming } else { ConnectionStatus::Connecting });
+
+            let mut shard = Shard::with_config(ShardId::ONE, builder.build());
+            let exit = Self::run_shard(&state, &mut shard, &config).await;
+
+            if let Ok(mut app) = state.lock() {
+                app.gateway = None;
+            }
+            let ShardExit::Stalled { connected } = exit else {
+                break;
+            };
+
+            // Resume where the replaced shard left off, unless Discord invalidated the session
+            session = shard.session().cloned();
+            attempt = if connected { 1 } else { attempt + 1 };
+            let delay = reconnect_delay(attempt);
+            eprintln!("Gateway stopped responding, reconnecting in {:?}", delay);
+            set_status(&state, ConnectionStatus::Reconnecting { attempt });
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Handles events until the shard is closed for good or stops answering heartbeats.
+    async fn run_shard(state: &Arc<Mutex<AppState>>, shard: &mut Shard, config: &GatewayConfig) -> ShardExit {
+        let mut limit = HELLO_TIMEOUT;
+        let mut connected = false;
+
+        loop {
+            let item = match tokio::time::timeout(limit, Self::next_event(shard)).await {
+                Ok(Some(item)) => item,
+                Ok(None) => {
+                    // A fatal close code already explained itself
+                    if let Ok(mut app) = state.lock()
+                        && app.connection.status != ConnectionStatus::Failed
+                    {
+                        app.connection.status = ConnectionStatus::Disconnected;
+                        app.revision += 1;
+                    }
+                    return ShardExit::Closed;
+                }
+                Err(_) => {
+                    // twilight is waiting out its own backoff, leave it to it
+                    if let ShardState::Disconnected { reconnect_attempts } = shard.state() {
+                        set_status(state, ConnectionStatus::Reconnecting { attempt: reconnect_attempts.into() });
+                        continue;
+                    }
+                    return ShardExit::Stalled { connected };
+                }
+            };
+
+            let (event, raw) = match item {
+                Ok(item) => item,
+                Err(e) => {
+                    eprintln!("Gateway error: {:?}", e);
+                    continue;
+                }
+            };
+
+            match &event {
+                Event::GatewayHello(hello) => {
+                    limit = stall_limit(std::time::Duration::from_millis(hello.heartbeat_interval));
+                    let status = if shard.session().is_some() { ConnectionStatus::Resuming } else { ConnectionStatus::Identifying };
+                    set_status(state, status);
+                }
+                Event::GatewayHeartbeatAck => {
+                    if let Ok(mut app) = state.lock() {
+                        app.connection.latency = shard.latency().average();
+                        app.revision += 1;
+                    }
+                }
+                Event::GatewayInvalidateSession(resumable) => {
+                    let status = if *resumable { ConnectionStatus::Resuming } else { ConnectionStatus::Identifying };
+                    set_status(state, status);
+                }
+                Event::GatewayReconnect => set_status(state, ConnectionStatus::Reconnecting { attempt: 0 }),
+                Event::GatewayClose(frame) => {
+                    let reason = frame.as_ref().and_then(|frame| {
+                        eprintln!("Gateway closed with {}: {}", frame.code, frame.reason);
+                        close_reason(frame.code, config)
+                    });
+                    if let Ok(mut app) = state.lock() {
+                        app.connection.status = match reason {
+                            Some(reason) => {
+                                app.error = Some(reason);
+                                ConnectionStatus::Failed
+                            }
+                            None => ConnectionStatus::Reconnecting { attempt: 0 },
+                        };
+                        app.revision += 1;
+                    }
+                }
+                Event::Ready(ready) => {
+                    connected = true;
+                    if let Ok(mut app) = state.lock() {
+                        // A new session after an old one means events were lost in between
+                        if app.session_id.is_some() {
+                            app.backfill_from = app.last_messages.clone();
+                            fill_open_channel_gap(state, &mut app);
+                            fill_direct_message_gaps(state, &mut app);
+                        }
+                        app.gateway = Some(shard.sender());
+                        app.session_id = Some(ready.session_id.clone());
+                        app.connection.status = ConnectionStatus::Connected;
+                        // Every identify starts out online, so put the chosen presence back
+                        app.current_user_id = Some(ready.user.id);
+                        app.load_presence();
+                        Self::send_presence(&app);
+                        app.revision += 1;
+                    }
+                }
+                Event::Resumed => {
+                    connected = true;
+                    if let Ok(mut app) = state.lock() {
+                        app.gateway = Some(shard.sender());
+                        fill_open_channel_gap(state, &mut app);
+                        app.connection.status = ConnectionStatus::Connected;
+                        app.revision += 1;
+                    }
+                }
+                _ => {}
+            }
+
+            Self::handle_event(state, event, raw);
+        }
+    }
+
+    /// Reads the next event like `next_event` does, also returning the raw `d` of an
+    /// INTERACTION_CREATE so the inbox shows the payload exactly as Discord sent it.
+    async fn next_event(shard: &mut Shard) -> Option<Result<(Event, Option<Value>), ReceiveMessageError>> {
+        loop {
+            let text = match shard.next().await? {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(frame)) => return Some(Ok((Event::GatewayClose(frame), None))),
+                Err(e) => return Some(Err(e)),
+            };
+            let raw = text.contains("\"INTERACTION_CREATE\"")
+                .then(|| serde_json::from_str::<Value>(&text).ok())
+                .flatten()
+                .filter(|payload| payload["t"] == "INTERACTION_CREATE")
+                .map(|mut payload| payload["d"].take());
+
+            match twilight_gateway::parse(text, EventTypeFlags::all()) {
+                Ok(Some(event)) => return Some(Ok((event.into(), raw))),
+                Ok(None) => {}
+                Err(e) => eprintln!("Gateway error: {:?}", e),
+            }
+        }
+    }
+
+    fn handle_event(state: &Arc<Mutex<AppState>>, event: Event, raw: Option<Value>) {
+        let Ok(mut app) = state.lock() else {
+            return;
+        };
+
+        match event {
+            Event::GuildCreate(guild) => {
+                if let twilight_model::gateway::payload::incoming::GuildCreate::Available(guild) = *guild {
+                    app.roles.insert(guild.id, guild.roles.iter().map(role_info).collect());
+                    if let Some(info) = app.guilds.iter_mut().find(|g| g.id == guild.id) {
+                        info.member_count = guild.member_count;
+                    }
+                    let members = app.members.entry(guild.id).or_default();
+                    for member in &guild.members {
+                        members.insert(member.user.id, member_info(guild.id, member));
+                    }
+                    apply_presences(&mut app, &guild.presences);
+                    if app.selected_guild == Some(guild.id) {
+                        Self::request_members(state, &mut app);
+                    }
+                    app.emojis.insert(guild.id, guild.emojis.iter().map(emoji_info).collect());
+                    for channel in &guild.channels {
+                        let last_message_id = channel.last_message_id.map(|id| id.cast());
+                        if let (Some(known), Some(newest)) = (app.backfill_from.remove(&channel.id), last_message_id)
+                            && newest > known
+                        {
+                            DiscordService::backfill_messages(state.clone(), channel.id, known);
+                        }
+                        app.record_last_message(channel.id, Some(guild.id), last_message_id);
+                        if let Some(name) = &channel.name {
+                            app.channel_names.insert(channel.id, name.clone());
+                        }
+                    }
+                }
+            }
+            Event::ChannelCreate(channel) => {
+                app.record_last_message(channel.id, channel.guild_id, channel.last_message_id.map(|id| id.cast()));
+            }
+            Event::MessageCreate(message) => {
+                let channel_id = message.channel_id;
+                let guild_id = message.guild_id;
+                let message_id = message.id;
+                let is_own = app.current_user_id == Some(message.author.id);
+
+                app.raise_last_message(channel_id, message_id);
+                // Sending a message ends the author's typing indicator
+                if let Some(users) = app.typing.get_mut(&channel_id) {
+                    users.remove(&message.author.id);
+                }
+                if let Some(guild_id) = guild_id {
+                    app.channel_guilds.insert(channel_id, guild_id);
+                }
+
+                let info = DiscordService::message_info(message.0, guild_id, &app);
+                let is_selected = app.selected_channel == Some(channel_id);
+
+                if info.mentions_me && !is_own {
+                    app.record_mention(MentionInfo {
+                        guild_id,
+                        channel_id,
+                        message: info.clone(),
+                    });
+                }
+
+                notify_message(&app, guild_id, channel_id, &info);
+
+                if is_selected {
+                    if !app.messages.iter().any(|msg| msg.id == message_id) {
+                        app.messages.push(info);
+                    }
+                    app.mark_channel_read(channel_id);
+                } else if is_own {
+                    app.mark_channel_read(channel_id);
+                }
+            }
+            Event::MessageUpdate(message) => {
+                // Bots edit their messages to reflect component interactions
+                if app.selected_channel == Some(message.channel_id) {
+                    let guild_id = message.guild_id;
+                    let info = DiscordService::message_info(message.0, guild_id, &app);
+                    if let Some(existing) = app.messages.iter_mut().find(|msg| msg.id == info.id) {
+                        *existing = info;
+                    }
+                }
+            }
+            Event::TypingStart(event) => {
+                let name = event.member.as_ref()
+                    .map(|member| display_name(member.nick.as_ref(), &member.user))
+                    .or_else(|| {
+                        event.guild_id
+                            .and_then(|guild_id| app.members.get(&guild_id))
+                            .and_then(|members| members.get(&event.user_id))
+                            .map(|member| member.display_name.clone())
+                    })
+                    .or_else(|| {
+                        app.profiles.get(&event.user_id)
+                            .map(|profile| profile.global_name.clone().unwrap_or_else(|| profile.username.clone()))
+                    })
+                    .or_else(|| {
+                        // Direct messages are named after the other person
+                        app.dm_channels.iter()
+                            .find(|channel| channel.id == event.channel_id)
+                            .map(|channel| channel.name.clone())
+                    })
+                    .unwrap_or_else(|| "Someone".to_string());
+                let started_at = DateTime::from_timestamp(event.timestamp as i64, 0).unwrap_or_else(Utc::now);
+
+                app.typing.entry(event.channel_id).or_default().insert(
+                    event.user_id,
+                    TypingInfo {
+                        name,
+                        expires_at: started_at + Duration::seconds(TYPING_EXPIRY_SECS),
+                    },
+                );
+            }
+            Event::GuildEmojisUpdate(event) => {
+                app.emojis.insert(event.guild_id, event.emojis.iter().map(emoji_info).collect());
+            }
+            Event::RoleCreate(event) => {
+                app.roles.entry(event.guild_id).or_default().push(role_info(&event.role));
+            }
+            Event::RoleUpdate(event) => {
+                let roles = app.roles.entry(event.guild_id).or_default();
+                roles.retain(|role| role.id != event.role.id);
+                roles.push(role_info(&event.role));
+            }
+            Event::RoleDelete(event) => {
+                if let Some(roles) = app.roles.get_mut(&event.guild_id) {
+                    roles.retain(|role| role.id != event.role_id);
+                }
+            }
+            Event::MemberChunk(chunk) => {
+                let members = app.members.entry(chunk.guild_id).or_default();
+                for member in &chunk.members {
+                    members.insert(member.user.id, member_info(chunk.guild_id, member));
+                }
+                apply_presences(&mut app, &chunk.presences);
+            }
+            Event::MemberAdd(event) => {
+                let info = member_info(event.guild_id, &event.member);
+                app.members.entry(event.guild_id).or_default().insert(info.user_id, info);
+            }
+            Event::MemberUpdate(event) => {
+                if let Some(member) = app.members.get_mut(&event.guild_id).and_then(|m| m.get_mut(&event.user.id)) {
+                    member.display_name = display_name(event.nick.as_ref(), &event.user);
+                    member.username = event.user.name.clone();
+                    member.avatar_url = event.avatar
+                        .map(|hash| guild_avatar_url(event.guild_id, event.user.id, hash))
+                        .or_else(|| event.user.avatar.map(|hash| avatar_url(event.user.id, hash)));
+                    member.roles = event.roles.clone();
+                }
+            }
+            Event::MemberRemove(event) => {
+                if let Some(members) = app.members.get_mut(&event.guild_id) {
+                    members.remove(&event.user.id);
+                }
+            }
+            Event::PresenceUpdate(event) => {
+                app.presences.insert(event.0.user.id(), event.0.status);
+            }
+            Event::InteractionCreate(event) => {
+                let Some(mut info) = raw.and_then(|payload| interaction_info(&event.0, payload)) else {
+                    return;
+                };
+                let defer = match info.kind {
+                    COMMAND_INTERACTION | MODAL_SUBMIT_INTERACTION => Some(json!({ "type": 5 })),
+                    COMPONENT_INTERACTION => Some(json!({ "type": 6 })),
+                    _ => None,
+                };
+                if let Some(defer) = defer.filter(|_| app.auto_defer_interactions) {
+                    // Marks it deferred before it shows up, so the inbox can't respond a second time
+                    DiscordService::respond_to_interaction(state.clone(), &mut info, defer);
+                }
+
+                app.interactions.insert(0, info);
+                app.interactions.truncate(INTERACTION_INBOX_LIMIT);
+            }
+            _ => return,
+        }
+
+        app.revision += 1;
+    }
+
+    /// Sends the account's presence to the gateway, if connected.
+    pub fn send_presence(app: &AppState) {
+        let Some(sender) = &app.gateway else {
+            return;
+        };
+        if let Err(e) = sender.command(&app.presence.update_presence()) {
+            eprintln!("Failed to update presence: {:?}", e);
+        }
+    }
+
+    /// Starts loading the selected guild's member list once it's shown. Small guilds
+    /// are requested whole over the gateway, large ones page in over REST as the list
+    /// is scrolled, see `DiscordService::fetch_member_page`.
+    pub fn request_members(state: &Arc<Mutex<AppState>>, app: &mut AppState) {
+        let Some(guild_id) = app.selected_guild.filter(|_| app.show_member_list) else {
+            return;
+        };
+        if app.member_pages.contains_key(&guild_id) {
+            return;
+        }
+        // Chunks can be turned off, and listing members needs the privileged intent
+        let config = &app.gateway_config;
+        if !config.request_member_chunks || !config.intents().contains(Intents::GUILD_MEMBERS) {
+            return;
+        }
+        if app.is_large_guild(guild_id) {
+            DiscordService::fetch_member_page(state, app, guild_id);
+            return;
+        }
+        // Asked again once the guild arrives if the gateway isn't up yet
+        let Some(sender) = app.gateway.clone() else {
+            return;
+        };
+
+        // A limit of 0 with an empty query returns every member
+        let request = RequestGuildMembers::builder(guild_id)
+            .presences(true)
+            .query("", Some(0));
+        if let Err(e) = sender.command(&request) {
+            eprintln!("Error requesting guild members: {:?}", e);
+            return;
+        }
+        app.member_pages.insert(guild_id, MemberPages { complete: true, ..Default::default() });
+    }
+
+    /// Asks Discord for members whose username or nickname starts with `query`.
+    pub fn search_members(state: &Arc<Mutex<AppState>>, guild_id: Id<GuildMarker>, query: &str) {
+        let sender = state.lock().ok().and_then(|app| app.gateway.clone());
+
+        if let Some(sender) = sender {
+            let request = RequestGuildMembers::builder(guild_id)
+                .presences(true)
+                .query(query, Some(MEMBER_SEARCH_LIMIT));
+            if let Err(e) = sender.command(&request) {
+                eprintln!("Error searching guild members: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Fetches what the open channel missed since its newest message. Guild channels
+/// are checked as their guilds arrive with the newest message ids, DMs right away.
+fn fill_open_channel_gap(state: &Arc<Mutex<AppState>>, app: &mut AppState) {
+    let Some(channel_id) = app.selected_channel else {
+        return;
+    };
+    app.backfill_from.remove(&channel_id);
+    let newest = app.messages.iter()
+        .map(|msg| msg.id)
+        .max()
+        .or_else(|| app.last_messages.get(&channel_id).copied());
+    if let Some(newest) = newest {
+        DiscordService::backfill_messages(state.clone(), channel_id, newest);
+    }
+}
+
+/// DMs aren't part of any GUILD_CREATE, so nothing reports their newest message
+/// after a new session. Fetch whatever came in after the last one we saw.
+fn fill_direct_message_gaps(state: &Arc<Mutex<AppState>>, app: &mut AppState) {
+    let direct: Vec<_> = app.backfill_from
+        .iter()
+        .filter(|(channel_id, _)| !app.channel_guilds.contains_key(channel_id))
+        .map(|(channel_id, message_id)| (*channel_id, *message_id))
+        .collect();
+    for (channel_id, after) in direct {
+        app.backfill_from.remove(&channel_id);
+        DiscordService::backfill_messages(state.clone(), channel_id, after);
+    }
+}
+
+fn set_status(state: &Arc<Mutex<AppState>>, status: ConnectionStatus) {
+    if let Ok(mut app) = state.lock()
+        && app.connection.status != status
+    {
+        app.connection.status = status;
+        app.revision += 1;
+    }
+}
+
+fn apply_presences(app: &mut AppState, presences: &[Presence]) {
+    for presence in presences {
+        app.presences.insert(presence.user.id(), presence.status);
+    }
+}
+
+/// Hands a new message to the notifier if the user's settings call for it.
+fn notify_message(
+    app: &AppState,
+    guild_id: Option<Id<GuildMarker>>,
+    channel_id: Id<ChannelMarker>,
+    info: &MessageInfo,
+) {
+    let candidate = MessageCandidate {
+        guild_id,
+        channel_id,
+        author_id: info.author_id,
+        mentions_me: info.mentions_me,
+    };
+    let context = NotifyContext {
+        current_user_id: app.current_user_id,
+        selected_channel: app.selected_channel,
+        window_active: app.window_active,
+        now: Utc::now(),
+    };
+    if !should_notify(&candidate, &context, &app.notification_settings) {
+        return;
+    }
+
+    let channel = app.channel_names.get(&channel_id).map(|name| format!("#{}", name));
+    let title = match (guild_id.and_then(|id| app.guilds.iter().find(|g| g.id == id)), channel) {
+        (Some(guild), Some(channel)) => format!("{} ({}, {})", info.author_name, channel, guild.name),
+        _ => info.author_name.clone(),
+    };
+    let body = if info.content.is_empty() && !info.attachments.is_empty() {
+        "Sent an attachment".to_string()
+    } else {
+        info.content.clone()
+    };
+
+    app.notifier.dispatch(
+        &Notification {
+            title,
+            body,
+            channel_id,
+            message_id: info.id,
+        },
+        app.window_active,
+    );
+}
+
+fn emoji_info(emoji: &Emoji) -> EmojiInfo {
+    EmojiInfo {
+        id: emoji.id,
+        name: emoji.name.clone(),
+        animated: emoji.animated,
+    }
+}
+
+fn role_info(role: &Role) -> RoleInfo {
+    RoleInfo {
+        id: role.id,
+        name: role.name.clone(),
+        color: role.colors.primary_color,
+        position: role.position,
+        hoist: role.hoist,
+    }
+}
+
+pub fn member_info(guild_id: Id<GuildMarker>, member: &Member) -> MemberInfo {
+    let avatar_url = member.avatar
+        .map(|hash| guild_avatar_url(guild_id, member.user.id, hash))
+        .or_else(|| member.user.avatar.map(|hash| avatar_url(member.user.id, hash)));
+
+    MemberInfo {
+        user_id: member.user.id,
+        username: member.user.name.clone(),
+        display_name: display_name(member.nick.as_ref(), &member.user),
+        avatar_url,
+        roles: member.roles.clone(),
+        bot: member.user.bot,
+        joined_at: member.joined_at.map(to_datetime),
+    }
+}
+
+/// Keeps the whole payload so the inbox can show exactly what the bot received.
+fn interaction_info(interaction: &Interaction, payload: Value) -> Option<InteractionInfo> {
+    let data = &payload["data"];
+    let label = data["name"].as_str()
+        .map(|name| format!("/{}", name))
+        .or_else(|| data["custom_id"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    let user = payload["member"]["user"]["username"].as_str()
+        .or_else(|| payload["user"]["username"].as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Some(InteractionInfo {
+        id: interaction.id.to_string(),
+        token: interaction.token.clone(),
+        application_id: interaction.application_id.to_string(),
+        kind: payload["type"].as_u64()?,
+        label,
+        user,
+        received_at: Utc::now(),
+        payload,
+        state: InteractionResponseState::Pending,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fake_server::{self, Fault, Log, Logged};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn resumes_after_disconnects_and_identifies_after_invalid_session() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let log = Log::default();
+        let faults = [
+            Fault::Drop,
+            Fault::Close(4000),
+            Fault::Reconnect,
+            Fault::InvalidSession { resumable: false },
+        ];
+        tokio::spawn(fake_server::serve(
+            listener,
+            move |connection| faults.get(connection).copied(),
+            std::time::Duration::from_millis(500),
+            log.clone(),
+        ));
+
+        let state = Arc::new(Mutex::new(AppState::new()));
+        let config = GatewayConfig::default();
+        let builder = ConfigBuilder::new("fake-token".to_string(), config.intents()).proxy_url(url);
+        let mut shard = Shard::with_config(ShardId::ONE, builder.build());
+
+        // Done once the session from the second identify is ready
+        let reidentified = async {
+            loop {
+                let second = match log.lock().unwrap().get(4) {
+                    Some(Logged::Identify { session_id }) => Some(session_id.clone()),
+                    _ => None,
+                };
+                if second.is_some() && state.lock().unwrap().session_id == second {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        };
+        // twilight waits 5 seconds between identifies
+        let finished = tokio::time::timeout(std::time::Duration::from_secs(30), async {
+            tokio::select! {
+                _ = GatewayService::run_shard(&state, &mut shard, &config) => panic!("the shard stopped"),
+                _ = reidentified => {}
+            }
+        })
+        .await;
+        let log = log.lock().unwrap().clone();
+        assert!(finished.is_ok(), "timed out, the fake gateway saw {:?}", log);
+
+        let [
+            Logged::Identify { session_id: first },
+            Logged::Resume { session_id: dropped, accepted: true },
+            Logged::Resume { session_id: closed, accepted: true },
+            Logged::Resume { session_id: reconnected, accepted: true },
+            Logged::Identify { session_id: second },
+        ] = log.as_slice() else {
+            panic!("expected identify, three resumes, identify, got {:?}", log);
+        };
+        assert!([dropped, closed, reconnected].iter().all(|resumed| *resumed == first));
+        assert_ne!(first, second);
+        assert_eq!(state.lock().unwrap().connection.status, ConnectionStatus::Connected);
+    }
+
+    #[tokio::test]
+    async fn replaces_a_zombie_shard_and_resumes() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let log = Log::default();
+        // twilight notices the missed ACKs itself, but its reconnect hangs too
+        let faults = [Fault::Zombie, Fault::Hang];
+        tokio::spawn(fake_server::serve(
+            listener,
+            move |connection| faults.get(connection).copied(),
+            std::time::Duration::from_millis(500),
+            log.clone(),
+        ));
+
+        let state = Arc::new(Mutex::new(AppState::new()));
+        let gateway = tokio::spawn(GatewayService::run(state.clone(), "fake-token".to_string(), Some(url)));
+
+        let mut replaced = false;
+        let resumed = tokio::time::timeout(std::time::Duration::from_secs(60), async {
+            loop {
+                let status = state.lock().unwrap().connection.status;
+                replaced |= status == ConnectionStatus::Reconnecting { attempt: 1 };
+                if replaced && status == ConnectionStatus::Connected {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        gateway.abort();
+        let log = log.lock().unwrap().clone();
+        assert!(resumed.is_ok(), "timed out, replaced: {}, the fake gateway saw {:?}", replaced, log);
+
+        let [
+            Logged::Identify { session_id: first },
+            Logged::Resume { session_id: resumed, accepted: true },
+        ] = log.as_slice() else {
+            panic!("expected identify, resume, got {:?}", log);
+        };
+        assert_eq!(first, resumed);
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use twilight_gateway::Intents;
+
+/// Intents offered in settings, with whether Discord treats them as privileged.
+/// Privileged intents have to be enabled for the bot in the Developer Portal.
+pub const INTENT_OPTIONS: [(Intents, &str, bool); 17] = [
+    (Intents::GUILDS, "Guilds", false),
+    (Intents::GUILD_MEMBERS, "Guild members", true),
+    (Intents::GUILD_MODERATION, "Moderation", false),
+    (Intents::GUILD_EMOJIS_AND_STICKERS, "Emojis and stickers", false),
+    (Intents::GUILD_INTEGRATIONS, "Integrations", false),
+    (Intents::GUILD_WEBHOOKS, "Webhooks", false),
+    (Intents::GUILD_INVITES, "Invites", false),
+    (Intents::GUILD_VOICE_STATES, "Voice states", false),
+    (Intents::GUILD_PRESENCES, "Presences", true),
+    (Intents::GUILD_MESSAGES, "Guild messages", false),
+    (Intents::GUILD_MESSAGE_REACTIONS, "Guild reactions", false),
+    (Intents::GUILD_MESSAGE_TYPING, "Guild typing", false),
+    (Intents::DIRECT_MESSAGES, "Direct messages", false),
+    (Intents::DIRECT_MESSAGE_REACTIONS, "DM reactions", false),
+    (Intents::DIRECT_MESSAGE_TYPING, "DM typing", false),
+    (Intents::MESSAGE_CONTENT, "Message content", true),
+    (Intents::GUILD_SCHEDULED_EVENTS, "Scheduled events", false),
+];
+
+/// Bounds Discord accepts for `large_threshold`.
+pub const LARGE_THRESHOLD_CHOICES: [u64; 4] = [50, 100, 150, 250];
+
+/// Transport compression is fixed at compile time by the `zstd` feature, which switches
+/// twilight-gateway from zlib to zstd. It isn't part of `GatewayConfig` because a
+/// build can only decompress the one it was built with.
+pub const COMPRESSION: &str = if cfg!(feature = "zstd") { "zstd-stream" } else { "zlib-stream" };
+
+/// Connection options for one account, applied the next time the gateway connects.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GatewayConfig {
+    /// Raw intent bits.
+    pub intents: u64,
+    /// Member count above which guilds arrive without their offline members.
+    pub large_threshold: u64,
+    /// Whether the member list loads members beyond the ones sent with the guild.
+    pub request_member_chunks: bool,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        let intents = Intents::GUILDS
+            | Intents::GUILD_MEMBERS
+            | Intents::GUILD_PRESENCES
+            | Intents::GUILD_MESSAGES
+            | Intents::DIRECT_MESSAGES
+            | Intents::MESSAGE_CONTENT
+            | Intents::GUILD_MESSAGE_TYPING
+            | Intents::DIRECT_MESSAGE_TYPING
+            | Intents::GUILD_EMOJIS_AND_STICKERS;
+        Self {
+            intents: intents.bits(),
+            large_threshold: 250,
+            request_member_chunks: true,
+        }
+    }
+}
+
+impl GatewayConfig {
+    pub fn intents(&self) -> Intents {
+        Intents::from_bits_truncate(self.intents)
+    }
+
+    pub fn toggle_intent(&mut self, intent: Intents) {
+        self.intents = (self.intents() ^ intent).bits();
+    }
+
+    /// Labels of the enabled intents that need approval in the Developer Portal.
+    pub fn privileged(&self) -> Vec<&'static str> {
+        let intents = self.intents();
+        INTENT_OPTIONS
+            .iter()
+            .filter(|(intent, _, privileged)| *privileged && intents.contains(*intent))
+            .map(|(_, label, _)| *label)
+            .collect()
+    }
+
+    pub fn large_threshold(&self) -> u64 {
+        self.large_threshold.clamp(50, 250)
+    }
+}
+
+/// Explains the close codes that stop the gateway for good.
+pub fn close_reason(code: u16, config: &GatewayConfig) -> Option<String> {
+    match code {
+        4004 => Some("Discord rejected the token. Log in again with a valid token.".to_string()),
+        4013 => Some("Discord rejected the gateway intents as invalid. Check the intents in Settings.".to_string()),
+        4014 => {
+            let privileged = config.privileged();
+            Some(format!(
+                "Discord closed the connection because the bot isn't allowed these privileged intents: {}. \
+                 Enable them under Bot > Privileged Gateway Intents in the Developer Portal, or turn them off in Settings.",
+                if privileged.is_empty() { "unknown".to_string() } else { privileged.join(", ") }
+            ))
+        }
+        _ => None,
+    }
+}
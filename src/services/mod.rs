@@ -1,2 +1,10 @@
+pub mod command_validation;
+pub mod connection;
 pub mod discord;
-
+pub mod gateway;
+pub mod gateway_config;
+pub mod notifications;
+pub mod presence;
+pub mod rest;
+pub mod scheduler;
+pub mod storage;
@@ -0,0 +1,382 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use twilight_model::id::marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker};
+use twilight_model::id::Id;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum NotificationLevel {
+    All,
+    MentionsOnly,
+    Nothing,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum MuteUntil {
+    Time(DateTime<Utc>),
+    Forever,
+}
+
+/// Mute lengths offered in the UI.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MuteDuration {
+    FifteenMinutes,
+    OneHour,
+    EightHours,
+    TwentyFourHours,
+    Forever,
+}
+
+impl MuteDuration {
+    pub const ALL: [MuteDuration; 5] = [
+        MuteDuration::FifteenMinutes,
+        MuteDuration::OneHour,
+        MuteDuration::EightHours,
+        MuteDuration::TwentyFourHours,
+        MuteDuration::Forever,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MuteDuration::FifteenMinutes => "15 minutes",
+            MuteDuration::OneHour => "1 hour",
+            MuteDuration::EightHours => "8 hours",
+            MuteDuration::TwentyFourHours => "24 hours",
+            MuteDuration::Forever => "Until I turn it back on",
+        }
+    }
+
+    pub fn until(&self, now: DateTime<Utc>) -> MuteUntil {
+        let minutes = match self {
+            MuteDuration::FifteenMinutes => 15,
+            MuteDuration::OneHour => 60,
+            MuteDuration::EightHours => 8 * 60,
+            MuteDuration::TwentyFourHours => 24 * 60,
+            MuteDuration::Forever => return MuteUntil::Forever,
+        };
+        MuteUntil::Time(now + Duration::minutes(minutes))
+    }
+}
+
+impl MuteUntil {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            MuteUntil::Time(until) => *until > now,
+            MuteUntil::Forever => true,
+        }
+    }
+}
+
+/// Overrides for a single guild or channel. `None` falls through to the parent scope.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScopeSettings {
+    pub level: Option<NotificationLevel>,
+    pub muted: Option<MuteUntil>,
+    /// Guild only: ignore @everyone and @here.
+    pub suppress_everyone: bool,
+    /// Guild only: ignore mentions of our roles.
+    pub suppress_roles: bool,
+}
+
+impl ScopeSettings {
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted.map(|mute| mute.is_active(now)).unwrap_or(false)
+    }
+
+    /// Whether a message pings us once this guild's suppression is applied. Direct
+    /// mentions always count.
+    pub fn counts_as_mention(&self, direct: bool, everyone: bool, role: bool) -> bool {
+        direct || (everyone && !self.suppress_everyone) || (role && !self.suppress_roles)
+    }
+}
+
+/// Mute and notification overrides, stored per account on this device. They aren't
+/// synced: Discord keeps user guild settings for user accounts only, and the client
+/// logs in as a bot.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub guilds: HashMap<Id<GuildMarker>, ScopeSettings>,
+    pub channels: HashMap<Id<ChannelMarker>, ScopeSettings>,
+}
+
+impl NotificationSettings {
+    pub fn guild(&self, guild_id: Id<GuildMarker>) -> ScopeSettings {
+        self.guilds.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    pub fn channel(&self, channel_id: Id<ChannelMarker>) -> ScopeSettings {
+        self.channels.get(&channel_id).cloned().unwrap_or_default()
+    }
+
+    /// Effective level for a channel: channel override, then guild override, then the
+    /// default of everything for DMs and mentions only for guilds.
+    pub fn level(&self, guild_id: Option<Id<GuildMarker>>, channel_id: Id<ChannelMarker>) -> NotificationLevel {
+        self.channels.get(&channel_id).and_then(|scope| scope.level)
+            .or_else(|| guild_id.and_then(|id| self.guilds.get(&id)).and_then(|scope| scope.level))
+            .unwrap_or(if guild_id.is_some() {
+                NotificationLevel::MentionsOnly
+            } else {
+                NotificationLevel::All
+            })
+    }
+
+    pub fn is_muted(&self, guild_id: Option<Id<GuildMarker>>, channel_id: Id<ChannelMarker>, now: DateTime<Utc>) -> bool {
+        self.channels.get(&channel_id).map(|scope| scope.is_muted(now)).unwrap_or(false)
+            || guild_id
+                .and_then(|id| self.guilds.get(&id))
+                .map(|scope| scope.is_muted(now))
+                .unwrap_or(false)
+    }
+}
+
+/// What we know about an incoming message when deciding whether to notify.
+pub struct MessageCandidate {
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub channel_id: Id<ChannelMarker>,
+    pub author_id: Id<UserMarker>,
+    pub mentions_me: bool,
+}
+
+/// Client state the decision depends on.
+pub struct NotifyContext {
+    pub current_user_id: Option<Id<UserMarker>>,
+    pub selected_channel: Option<Id<ChannelMarker>>,
+    pub window_active: bool,
+    pub now: DateTime<Utc>,
+}
+
+/// Pure notification decision, kept free of gpui and desktop APIs.
+pub fn should_notify(candidate: &MessageCandidate, context: &NotifyContext, settings: &NotificationSettings) -> bool {
+    if context.current_user_id == Some(candidate.author_id) {
+        return false;
+    }
+    // Already looking at it
+    if context.window_active && context.selected_channel == Some(candidate.channel_id) {
+        return false;
+    }
+    if settings.is_muted(candidate.guild_id, candidate.channel_id, context.now) {
+        return false;
+    }
+
+    match settings.level(candidate.guild_id, candidate.channel_id) {
+        NotificationLevel::All => true,
+        NotificationLevel::MentionsOnly => candidate.mentions_me,
+        NotificationLevel::Nothing => false,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    pub channel_id: Id<ChannelMarker>,
+    pub message_id: Id<MessageMarker>,
+}
+
+pub trait NotificationBackend: Send + Sync {
+    fn notify(&self, notification: &Notification, window_active: bool);
+}
+
+/// Queues in-app toasts; the channel view's refresh task drains the queue into
+/// gpui-component notifications.
+pub struct ToastBackend {
+    queue: Arc<Mutex<Vec<Notification>>>,
+}
+
+impl ToastBackend {
+    pub fn new(queue: Arc<Mutex<Vec<Notification>>>) -> Self {
+        Self { queue }
+    }
+}
+
+impl NotificationBackend for ToastBackend {
+    fn notify(&self, notification: &Notification, window_active: bool) {
+        if !window_active {
+            return;
+        }
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.push(notification.clone());
+        }
+    }
+}
+
+/// Desktop notifications through the freedesktop notification service.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub struct FreedesktopBackend;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl NotificationBackend for FreedesktopBackend {
+    fn notify(&self, notification: &Notification, window_active: bool) {
+        if window_active {
+            return;
+        }
+        let mut desktop = notify_rust::Notification::new();
+        desktop
+            .appname("Oxidecord")
+            .summary(&notification.title)
+            .body(&notification.body);
+        // show() waits on D-Bus, and dispatch runs with the app state locked
+        crate::utils::get_runtime_handle().spawn_blocking(move || {
+            if let Err(e) = desktop.show() {
+                eprintln!("Error showing desktop notification: {:?}", e);
+            }
+        });
+    }
+}
+
+pub struct Notifier {
+    backends: Vec<Box<dyn NotificationBackend>>,
+}
+
+impl Notifier {
+    pub fn new(toasts: Arc<Mutex<Vec<Notification>>>) -> Self {
+        let mut backends: Vec<Box<dyn NotificationBackend>> = vec![Box::new(ToastBackend::new(toasts))];
+        #[cfg(all(unix, not(target_os = "macos")))]
+        backends.push(Box::new(FreedesktopBackend));
+
+        Self { backends }
+    }
+
+    pub fn dispatch(&self, notification: &Notification, window_active: bool) {
+        for backend in &self.backends {
+            backend.notify(notification, window_active);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ME: u64 = 1;
+    const OTHER: u64 = 2;
+    const GUILD: u64 = 10;
+    const CHANNEL: u64 = 20;
+
+    fn candidate(guild: Option<u64>, author: u64, mentions_me: bool) -> MessageCandidate {
+        MessageCandidate {
+            guild_id: guild.map(Id::new),
+            channel_id: Id::new(CHANNEL),
+            author_id: Id::new(author),
+            mentions_me,
+        }
+    }
+
+    fn context() -> NotifyContext {
+        NotifyContext {
+            current_user_id: Some(Id::new(ME)),
+            selected_channel: None,
+            window_active: false,
+            now: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn dms_notify_by_default() {
+        let settings = NotificationSettings::default();
+        assert!(should_notify(&candidate(None, OTHER, false), &context(), &settings));
+    }
+
+    #[test]
+    fn own_messages_never_notify() {
+        let settings = NotificationSettings::default();
+        assert!(!should_notify(&candidate(None, ME, false), &context(), &settings));
+        assert!(!should_notify(&candidate(Some(GUILD), ME, true), &context(), &settings));
+    }
+
+    #[test]
+    fn guilds_default_to_mentions_only() {
+        let settings = NotificationSettings::default();
+        assert!(!should_notify(&candidate(Some(GUILD), OTHER, false), &context(), &settings));
+        assert!(should_notify(&candidate(Some(GUILD), OTHER, true), &context(), &settings));
+    }
+
+    #[test]
+    fn channel_level_overrides_guild_level() {
+        let mut settings = NotificationSettings::default();
+        settings.guilds.insert(Id::new(GUILD), ScopeSettings {
+            level: Some(NotificationLevel::Nothing),
+            ..Default::default()
+        });
+        assert!(!should_notify(&candidate(Some(GUILD), OTHER, true), &context(), &settings));
+
+        settings.channels.insert(Id::new(CHANNEL), ScopeSettings {
+            level: Some(NotificationLevel::All),
+            ..Default::default()
+        });
+        assert!(should_notify(&candidate(Some(GUILD), OTHER, false), &context(), &settings));
+    }
+
+    #[test]
+    fn mentions_only_channel_in_dm() {
+        let mut settings = NotificationSettings::default();
+        settings.channels.insert(Id::new(CHANNEL), ScopeSettings {
+            level: Some(NotificationLevel::MentionsOnly),
+            ..Default::default()
+        });
+        assert!(!should_notify(&candidate(None, OTHER, false), &context(), &settings));
+        assert!(should_notify(&candidate(None, OTHER, true), &context(), &settings));
+    }
+
+    #[test]
+    fn muted_scopes_suppress_even_mentions() {
+        let context = context();
+        let mut settings = NotificationSettings::default();
+        settings.guilds.insert(Id::new(GUILD), ScopeSettings {
+            muted: Some(MuteUntil::Forever),
+            ..Default::default()
+        });
+        assert!(!should_notify(&candidate(Some(GUILD), OTHER, true), &context, &settings));
+
+        let mut settings = NotificationSettings::default();
+        settings.channels.insert(Id::new(CHANNEL), ScopeSettings {
+            muted: Some(MuteDuration::OneHour.until(context.now)),
+            ..Default::default()
+        });
+        assert!(!should_notify(&candidate(None, OTHER, true), &context, &settings));
+    }
+
+    #[test]
+    fn expired_mutes_are_ignored() {
+        let context = context();
+        let mut settings = NotificationSettings::default();
+        settings.channels.insert(Id::new(CHANNEL), ScopeSettings {
+            muted: Some(MuteUntil::Time(context.now - Duration::minutes(1))),
+            ..Default::default()
+        });
+        assert!(should_notify(&candidate(None, OTHER, false), &context, &settings));
+    }
+
+    #[test]
+    fn suppression_drops_everyone_and_role_mentions() {
+        let open = ScopeSettings::default();
+        assert!(open.counts_as_mention(false, true, false));
+        assert!(open.counts_as_mention(false, false, true));
+
+        let suppressed = ScopeSettings {
+            suppress_everyone: true,
+            suppress_roles: true,
+            ..Default::default()
+        };
+        assert!(!suppressed.counts_as_mention(false, true, false));
+        assert!(!suppressed.counts_as_mention(false, false, true));
+        assert!(!suppressed.counts_as_mention(false, true, true));
+        assert!(suppressed.counts_as_mention(true, true, true));
+    }
+
+    #[test]
+    fn viewing_the_channel_suppresses() {
+        let settings = NotificationSettings::default();
+        let mut context = context();
+        context.selected_channel = Some(Id::new(CHANNEL));
+        context.window_active = true;
+        assert!(!should_notify(&candidate(None, OTHER, true), &context, &settings));
+
+        // Same channel, but the window is in the background
+        context.window_active = false;
+        assert!(should_notify(&candidate(None, OTHER, true), &context, &settings));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use twilight_model::gateway::OpCode;
+use twilight_model::gateway::payload::outgoing::update_presence::{UpdatePresence, UpdatePresencePayload};
+use twilight_model::gateway::presence::{Activity, ActivityType, MinimalActivity, Status};
+
+/// Statuses the account can pick for itself.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum OwnStatus {
+    #[default]
+    Online,
+    Idle,
+    DoNotDisturb,
+    Invisible,
+}
+
+impl OwnStatus {
+    pub const ALL: [OwnStatus; 4] = [
+        OwnStatus::Online,
+        OwnStatus::Idle,
+        OwnStatus::DoNotDisturb,
+        OwnStatus::Invisible,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OwnStatus::Online => "Online",
+            OwnStatus::Idle => "Idle",
+            OwnStatus::DoNotDisturb => "Do Not Disturb",
+            OwnStatus::Invisible => "Invisible",
+        }
+    }
+
+    pub fn color(&self) -> u32 {
+        match self {
+            OwnStatus::Online => 0x23a55a,
+            OwnStatus::Idle => 0xf0b232,
+            OwnStatus::DoNotDisturb => 0xf23f43,
+            OwnStatus::Invisible => 0x80848e,
+        }
+    }
+
+    fn status(&self) -> Status {
+        match self {
+            OwnStatus::Online => Status::Online,
+            OwnStatus::Idle => Status::Idle,
+            OwnStatus::DoNotDisturb => Status::DoNotDisturb,
+            OwnStatus::Invisible => Status::Invisible,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ActivityKind {
+    Playing,
+    Listening,
+    Watching,
+    Competing,
+    Custom,
+}
+
+impl ActivityKind {
+    pub const ALL: [ActivityKind; 5] = [
+        ActivityKind::Playing,
+        ActivityKind::Listening,
+        ActivityKind::Watching,
+        ActivityKind::Competing,
+        ActivityKind::Custom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActivityKind::Playing => "Playing",
+            ActivityKind::Listening => "Listening to",
+            ActivityKind::Watching => "Watching",
+            ActivityKind::Competing => "Competing in",
+            ActivityKind::Custom => "Custom",
+        }
+    }
+
+    fn activity_type(&self) -> ActivityType {
+        match self {
+            ActivityKind::Playing => ActivityType::Playing,
+            ActivityKind::Listening => ActivityType::Listening,
+            ActivityKind::Watching => ActivityType::Watching,
+            ActivityKind::Competing => ActivityType::Competing,
+            ActivityKind::Custom => ActivityType::Custom,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ActivitySetting {
+    pub kind: ActivityKind,
+    pub text: String,
+}
+
+/// The account's own presence, persisted per account and sent after every identify.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PresenceSettings {
+    pub status: OwnStatus,
+    pub activity: Option<ActivitySetting>,
+}
+
+impl PresenceSettings {
+    /// Short description for the status button, e.g. "Idle · Playing chess".
+    pub fn summary(&self) -> String {
+        match &self.activity {
+            Some(activity) if activity.kind == ActivityKind::Custom => format!("{} · {}", self.status.label(), activity.text),
+            Some(activity) => format!("{} · {} {}", self.status.label(), activity.kind.label(), activity.text),
+            None => self.status.label().to_string(),
+        }
+    }
+
+    /// Gateway command setting this presence. Built directly because
+    /// `UpdatePresence::new` refuses an empty activity list.
+    pub fn update_presence(&self) -> UpdatePresence {
+        let activities = self.activity
+            .iter()
+            .map(|setting| {
+                let mut activity = Activity::from(MinimalActivity {
+                    kind: setting.kind.activity_type(),
+                    name: setting.text.clone(),
+                    url: None,
+                });
+                // Custom statuses show their state, the name is required but unused
+                if setting.kind == ActivityKind::Custom {
+                    activity.name = "Custom Status".to_string();
+                    activity.state = Some(setting.text.clone());
+                }
+                activity
+            })
+            .collect();
+
+        UpdatePresence {
+            d: UpdatePresencePayload {
+                activities,
+                afk: self.status == OwnStatus::Idle,
+                since: None,
+                status: self.status.status(),
+            },
+            op: OpCode::PresenceUpdate,
+        }
+    }
+}
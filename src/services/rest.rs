@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+use crate::services::scheduler::{scheduler, Priority};
+
+const API_BASE: &str = "https://discord.com/api/v10";
+
+/// Requests for endpoints twilight-http has no route for, or whose bodies the
+/// developer console and interaction inbox pass through as raw JSON. Each one goes
+/// through the scheduler, with `route` naming its rate limit as for twilight requests.
+pub struct RawRest;
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// The client only logs in as a bot, so the token always gets the `Bot ` prefix.
+fn authorization(token: &str) -> String {
+    format!("Bot {}", token.trim().trim_start_matches("Bot "))
+}
+
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(anyhow!("{} {}", status, body))
+}
+
+impl RawRest {
+    pub async fn get<T: DeserializeOwned>(token: &str, priority: Priority, route: &str, path: &str) -> Result<T> {
+        let request = client()
+            .get(format!("{}{}", API_BASE, path))
+            .header("Authorization", authorization(token));
+        let response = scheduler().send_raw(priority, route, request.send()).await?;
+        Ok(check(response).await?.json().await?)
+    }
+
+    pub async fn post_json(token: &str, priority: Priority, route: &str, path: &str, body: &Value) -> Result<()> {
+        Self::send_json(reqwest::Method::POST, token, priority, route, path, body).await
+    }
+
+    pub async fn patch_json(token: &str, priority: Priority, route: &str, path: &str, body: &Value) -> Result<()> {
+        Self::send_json(reqwest::Method::PATCH, token, priority, route, path, body).await
+    }
+
+    pub async fn put_json(token: &str, priority: Priority, route: &str, path: &str, body: &Value) -> Result<()> {
+        Self::send_json(reqwest::Method::PUT, token, priority, route, path, body).await
+    }
+
+    pub async fn delete(token: &str, priority: Priority, route: &str, path: &str) -> Result<()> {
+        let request = client()
+            .delete(format!("{}{}", API_BASE, path))
+            .header("Authorization", authorization(token));
+        check(scheduler().send_raw(priority, route, request.send()).await?).await?;
+        Ok(())
+    }
+
+    async fn send_json(method: reqwest::Method, token: &str, priority: Priority, route: &str, path: &str, body: &Value) -> Result<()> {
+        let request = client()
+            .request(method, format!("{}{}", API_BASE, path))
+            .header("Authorization", authorization(token))
+            .json(body);
+        check(scheduler().send_raw(priority, route, request.send()).await?).await?;
+        Ok(())
+    }
+}
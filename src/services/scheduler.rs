@@ -0,0 +1,482 @@
+use std::collections::{HashMap, HashSet};
+use std::future::{Future, IntoFuture};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::AbortHandle;
+use twilight_http::error::ErrorType;
+use twilight_http::response::Response;
+
+/// Requests sent at the same time. Later ones queue by priority.
+const MAX_IN_FLIGHT: usize = 4;
+/// Requests a bucket keeps for user actions. Prefetches wait for the reset instead.
+const RESERVED_FOR_USER: u32 = 1;
+
+static SCHEDULER: OnceLock<RequestScheduler> = OnceLock::new();
+
+pub fn scheduler() -> &'static RequestScheduler {
+    SCHEDULER.get_or_init(RequestScheduler::default)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Priority {
+    /// Polling, gap filling and other work nobody is waiting on.
+    Prefetch,
+    /// Loading what the user navigated to.
+    Normal,
+    /// Sends and other actions the user just took.
+    User,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::User, Priority::Normal, Priority::Prefetch];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::Prefetch => "Prefetch",
+            Priority::Normal => "Normal",
+            Priority::User => "User",
+        }
+    }
+}
+
+/// A route's rate limit as Discord last reported it.
+#[derive(Clone, Debug)]
+pub struct BucketState {
+    pub route: String,
+    /// Discord's id for the bucket, shared by routes with the same limit.
+    pub bucket: Option<String>,
+    pub limit: u32,
+    pub remaining: u32,
+    pub resets_at: Instant,
+    /// 429 responses seen on this route.
+    pub limited: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SchedulerSnapshot {
+    pub in_flight: usize,
+    /// Queued requests per priority, in `Priority::ALL` order.
+    pub queued: [usize; 3],
+    /// Fetches skipped because the same one was already on its way.
+    pub coalesced: u64,
+    pub buckets: Vec<BucketState>,
+}
+
+struct Waiter {
+    priority: Priority,
+    order: u64,
+    tx: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct Inner {
+    in_flight: usize,
+    queue: Vec<Waiter>,
+    next_order: u64,
+    claimed: HashSet<String>,
+    coalesced: u64,
+    buckets: HashMap<String, BucketState>,
+}
+
+/// Orders REST calls made through twilight and `RawRest`. twilight's ratelimiter
+/// still enforces the limits on its requests; this decides who gets to use them first.
+#[derive(Default)]
+pub struct RequestScheduler {
+    inner: Mutex<Inner>,
+}
+
+impl RequestScheduler {
+    /// Sends `request` when its turn comes. `route` names the rate limit it counts
+    /// against, e.g. `GET channels/123/messages`.
+    pub async fn send<T>(
+        &self,
+        priority: Priority,
+        route: &str,
+        request: impl IntoFuture<Output = Result<Response<T>, twilight_http::Error>>,
+    ) -> Result<Response<T>, twilight_http::Error> {
+        if let Some(wait) = self.reserve_wait(priority, route) {
+            tokio::time::sleep(wait).await;
+        }
+        let _slot = self.acquire(priority).await;
+        let result = request.into_future().await;
+        match &result {
+            Ok(response) => self.record(route, response.headers()),
+            Err(e) => {
+                if let ErrorType::Response { status, .. } = e.kind()
+                    && status.get() == 429
+                {
+                    self.record_limited(route);
+                }
+            }
+        }
+        result
+    }
+
+    /// Like `send`, for the requests `RawRest` makes with reqwest. Discord answers a
+    /// 429 with a response rather than an error there, so it's recorded from the status.
+    pub async fn send_raw(
+        &self,
+        priority: Priority,
+        route: &str,
+        request: impl Future<Output = reqwest::Result<reqwest::Response>>,
+    ) -> reqwest::Result<reqwest::Response> {
+        if let Some(wait) = self.reserve_wait(priority, route) {
+            tokio::time::sleep(wait).await;
+        }
+        let _slot = self.acquire(priority).await;
+        let response = request.await?;
+        let headers = response.headers().iter().map(|(name, value)| (name.as_str(), value.as_bytes()));
+        self.record(route, headers);
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.record_limited(route);
+        }
+        Ok(response)
+    }
+
+    /// Claims a fetch by key. Returns `None` while the same fetch is still queued or
+    /// running, so repeated clicks don't send it twice.
+    pub fn claim(&'static self, key: String) -> Option<Claim> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.claimed.insert(key.clone()) {
+            inner.coalesced += 1;
+            return None;
+        }
+        Some(Claim { scheduler: self, key })
+    }
+
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        let mut queued = [0; 3];
+        for waiter in &inner.queue {
+            if let Some(index) = Priority::ALL.iter().position(|priority| *priority == waiter.priority) {
+                queued[index] += 1;
+            }
+        }
+        let mut buckets: Vec<_> = inner.buckets.values().cloned().collect();
+        buckets.sort_by(|a, b| a.route.cmp(&b.route));
+
+        SchedulerSnapshot {
+            in_flight: inner.in_flight,
+            queued,
+            coalesced: inner.coalesced,
+            buckets,
+        }
+    }
+
+    /// How long a non-user request should wait so the bucket keeps its reserve.
+    fn reserve_wait(&self, priority: Priority, route: &str) -> Option<Duration> {
+        if priority == Priority::User {
+            return None;
+        }
+        let inner = self.inner.lock().unwrap();
+        let bucket = inner.buckets.get(route)?;
+        let wait = bucket.resets_at.checked_duration_since(Instant::now())?;
+        (bucket.remaining <= RESERVED_FOR_USER).then_some(wait)
+    }
+
+    async fn acquire(&self, priority: Priority) -> Slot<'_> {
+        let rx = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.in_flight < MAX_IN_FLIGHT && inner.queue.is_empty() {
+                inner.in_flight += 1;
+                return Slot { scheduler: self };
+            }
+            let (tx, rx) = oneshot::channel();
+            let order = inner.next_order;
+            inner.next_order += 1;
+            inner.queue.push(Waiter { priority, order, tx });
+            rx
+        };
+
+        let mut queued = Queued { scheduler: self, rx, granted: false };
+        // The sender only goes away with the scheduler, which lives forever
+        let _ = (&mut queued.rx).await;
+        queued.granted = true;
+        Slot { scheduler: self }
+    }
+
+    /// Frees a slot and hands it to the most important waiter still around.
+    fn release(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.in_flight -= 1;
+        while let Some(index) = inner.queue
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, waiter)| (waiter.priority, std::cmp::Reverse(waiter.order)))
+            .map(|(index, _)| index)
+        {
+            let waiter = inner.queue.remove(index);
+            // Waiters whose task was aborted have dropped their receiver
+            if waiter.tx.send(()).is_ok() {
+                inner.in_flight += 1;
+                break;
+            }
+        }
+    }
+
+    /// Updates the route's bucket from a response's `x-ratelimit-*` headers.
+    fn record<'a>(&self, route: &str, headers: impl Iterator<Item = (&'a str, &'a [u8])>) {
+        let mut bucket = None;
+        let mut limit = None;
+        let mut remaining = None;
+        let mut reset_after = None;
+        for (name, value) in headers {
+            let value = std::str::from_utf8(value).unwrap_or_default();
+            match name {
+                "x-ratelimit-bucket" => bucket = Some(value.to_string()),
+                "x-ratelimit-limit" => limit = value.parse().ok(),
+                "x-ratelimit-remaining" => remaining = value.parse().ok(),
+                "x-ratelimit-reset-after" => reset_after = value.parse::<f64>().ok(),
+                _ => {}
+            }
+        }
+        let (Some(limit), Some(remaining), Some(reset_after)) = (limit, remaining, reset_after) else {
+            return;
+        };
+        let mut inner = self.inner.lock().unwrap();
+        let state = inner.buckets.entry(route.to_string()).or_insert_with(|| BucketState {
+            route: route.to_string(),
+            bucket: None,
+            limit,
+            remaining,
+            resets_at: Instant::now(),
+            limited: 0,
+        });
+        state.bucket = bucket;
+        state.limit = limit;
+        state.remaining = remaining;
+        state.resets_at = Instant::now() + Duration::from_secs_f64(reset_after);
+    }
+
+    fn record_limited(&self, route: &str) {
+        if let Some(state) = self.inner.lock().unwrap().buckets.get_mut(route) {
+            state.remaining = 0;
+            state.limited += 1;
+        }
+    }
+}
+
+/// A claimed fetch, released when dropped.
+pub struct Claim {
+    scheduler: &'static RequestScheduler,
+    key: String,
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        self.scheduler.inner.lock().unwrap().claimed.remove(&self.key);
+    }
+}
+
+/// The fetch running for the current selection, e.g. the selected channel's messages.
+/// Holds the fetch's claim, so it can be sent again as soon as this is aborted.
+pub struct PendingFetch {
+    generation: u64,
+    handle: AbortHandle,
+    _claim: Claim,
+}
+
+impl PendingFetch {
+    pub fn new(generation: u64, handle: AbortHandle, claim: Claim) -> Self {
+        Self { generation, handle, _claim: claim }
+    }
+
+    /// Stops the fetch because the selection moved on.
+    pub fn abort(self) {
+        self.handle.abort();
+    }
+
+    /// Called by a finished fetch. Clears `pending` and returns true if the fetch is
+    /// still the current one, false if its response is stale and must be dropped.
+    pub fn finish(pending: &mut Option<PendingFetch>, generation: u64) -> bool {
+        if pending.as_ref().is_some_and(|fetch| fetch.generation == generation) {
+            *pending = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct Slot<'a> {
+    scheduler: &'a RequestScheduler,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// A place in the queue. If the task is aborted just after being handed a slot,
+/// the slot is passed on instead of being lost.
+struct Queued<'a> {
+    scheduler: &'a RequestScheduler,
+    rx: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        self.rx.close();
+        if self.rx.try_recv().is_ok() {
+            self.scheduler.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A scheduler of its own for each test, leaked like the global one.
+    fn new_scheduler() -> &'static RequestScheduler {
+        Box::leak(Box::default())
+    }
+
+    /// Takes every slot so later requests have to queue.
+    async fn fill(scheduler: &'static RequestScheduler) -> Vec<Slot<'static>> {
+        let mut slots = Vec::new();
+        for _ in 0..MAX_IN_FLIGHT {
+            slots.push(scheduler.acquire(Priority::User).await);
+        }
+        slots
+    }
+
+    /// Yields until `count` requests are queued.
+    async fn wait_queued(scheduler: &RequestScheduler, count: usize) {
+        while scheduler.snapshot().queued.iter().sum::<usize>() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn waiters_run_by_priority_then_order() {
+        let scheduler = new_scheduler();
+        let mut slots = fill(scheduler).await;
+        let served = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tasks = Vec::new();
+        for (name, priority) in [
+            ("prefetch", Priority::Prefetch),
+            ("normal first", Priority::Normal),
+            ("user", Priority::User),
+            ("normal second", Priority::Normal),
+        ] {
+            let served = served.clone();
+            tasks.push(tokio::spawn(async move {
+                let _slot = scheduler.acquire(priority).await;
+                served.lock().unwrap().push(name);
+            }));
+            wait_queued(scheduler, tasks.len()).await;
+        }
+        assert_eq!(scheduler.snapshot().queued, [1, 2, 1]);
+
+        // One slot, so each waiter only runs once the previous one is done
+        slots.pop();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*served.lock().unwrap(), ["user", "normal first", "normal second", "prefetch"]);
+
+        drop(slots);
+        assert_eq!(scheduler.snapshot().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn aborted_waiter_passes_its_slot_on() {
+        let scheduler = new_scheduler();
+        let mut slots = fill(scheduler).await;
+
+        let aborted = tokio::spawn(async move {
+            let _slot = scheduler.acquire(Priority::User).await;
+            unreachable!("an aborted waiter never runs");
+        });
+        wait_queued(scheduler, 1).await;
+        let next = tokio::spawn(async move {
+            let _slot = scheduler.acquire(Priority::Normal).await;
+        });
+        wait_queued(scheduler, 2).await;
+
+        // The slot goes to the user waiter, which is aborted before it gets to run
+        slots.pop();
+        assert_eq!(scheduler.snapshot().in_flight, MAX_IN_FLIGHT);
+        aborted.abort();
+        assert!(aborted.await.unwrap_err().is_cancelled());
+
+        next.await.unwrap();
+        drop(slots);
+        let snapshot = scheduler.snapshot();
+        assert_eq!(snapshot.in_flight, 0);
+        assert_eq!(snapshot.queued, [0, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn waiter_aborted_while_queued_is_skipped() {
+        let scheduler = new_scheduler();
+        let mut slots = fill(scheduler).await;
+
+        let aborted = tokio::spawn(async move {
+            let _slot = scheduler.acquire(Priority::User).await;
+            unreachable!("an aborted waiter never runs");
+        });
+        wait_queued(scheduler, 1).await;
+        let next = tokio::spawn(async move {
+            let _slot = scheduler.acquire(Priority::Normal).await;
+        });
+        wait_queued(scheduler, 2).await;
+
+        aborted.abort();
+        assert!(aborted.await.unwrap_err().is_cancelled());
+        slots.pop();
+
+        next.await.unwrap();
+        drop(slots);
+        assert_eq!(scheduler.snapshot().in_flight, 0);
+    }
+
+    #[test]
+    fn claims_coalesce_until_released() {
+        let scheduler = new_scheduler();
+
+        let claim = scheduler.claim("messages:1".to_string());
+        assert!(claim.is_some());
+        assert!(scheduler.claim("messages:1".to_string()).is_none());
+        assert!(scheduler.claim("messages:1".to_string()).is_none());
+        // Other fetches aren't held up
+        assert!(scheduler.claim("messages:2".to_string()).is_some());
+        assert_eq!(scheduler.snapshot().coalesced, 2);
+
+        drop(claim);
+        assert!(scheduler.claim("messages:1".to_string()).is_some());
+        assert_eq!(scheduler.snapshot().coalesced, 2);
+    }
+
+    #[test]
+    fn headers_and_429s_update_the_bucket() {
+        let scheduler = new_scheduler();
+        let route = "POST interactions/1/:token/callback";
+        // A 429 before any headers has no bucket to count against
+        scheduler.record_limited(route);
+        assert!(scheduler.snapshot().buckets.is_empty());
+
+        let headers = [
+            ("x-ratelimit-bucket", "abc".as_bytes()),
+            ("x-ratelimit-limit", b"5"),
+            ("x-ratelimit-remaining", b"3"),
+            ("x-ratelimit-reset-after", b"1.5"),
+        ];
+        scheduler.record(route, headers.into_iter());
+        scheduler.record_limited(route);
+
+        let [bucket] = scheduler.snapshot().buckets.try_into().unwrap();
+        assert_eq!(bucket.route, route);
+        assert_eq!(bucket.bucket.as_deref(), Some("abc"));
+        assert_eq!((bucket.limit, bucket.remaining, bucket.limited), (5, 0, 1));
+    }
+}
@@ -0,0 +1,95 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::utils::get_runtime_handle;
+
+/// How long `save_later` waits for more changes before writing.
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Contents waiting to be written by `save_later`, by key.
+static PENDING: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Small JSON file store under the platform config directory, one file per key.
+pub struct Storage;
+
+impl Storage {
+    fn path(name: &str) -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("oxidecord").join(format!("{}.json", name)))
+    }
+
+    /// Loads a stored value, falling back to the default when it is missing or unreadable.
+    pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+        let Some(path) = Self::path(name) else {
+            return T::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Error parsing {}: {:?}", path.display(), e);
+                T::default()
+            }),
+            Err(_) => T::default(),
+        }
+    }
+
+    pub fn save<T: Serialize>(name: &str, value: &T) {
+        match serde_json::to_string_pretty(value) {
+            Ok(contents) => Self::write(name, contents),
+            Err(e) => eprintln!("Error serializing {}: {:?}", name, e),
+        }
+    }
+
+    /// Saves a value that changes often. Changes are collected for `SAVE_DELAY` and
+    /// written together, on the runtime instead of the caller's thread.
+    pub fn save_later<T: Serialize>(name: &str, value: &T) {
+        let contents = match serde_json::to_string_pretty(value) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Error serializing {}: {:?}", name, e);
+                return;
+            }
+        };
+        let scheduled = {
+            let mut pending = PENDING.lock().unwrap();
+            pending.get_or_insert_with(HashMap::new).insert(name.to_string(), contents).is_some()
+        };
+        if scheduled {
+            return;
+        }
+
+        let name = name.to_string();
+        get_runtime_handle().spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            let contents = PENDING.lock().unwrap().as_mut().and_then(|pending| pending.remove(&name));
+            if let Some(contents) = contents {
+                let _ = tokio::task::spawn_blocking(move || Self::write(&name, contents)).await;
+            }
+        });
+    }
+
+    /// Writes everything `save_later` still holds. Called when the app quits.
+    pub fn flush() {
+        let pending = PENDING.lock().unwrap().take().unwrap_or_default();
+        for (name, contents) in pending {
+            Self::write(&name, contents);
+        }
+    }
+
+    fn write(name: &str, contents: String) {
+        let Some(path) = Self::path(name) else {
+            return;
+        };
+
+        let result = path.parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| fs::write(&path, contents));
+        if let Err(e) = result {
+            eprintln!("Error saving {}: {:?}", path.display(), e);
+        }
+    }
+}
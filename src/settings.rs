@@ -0,0 +1,124 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use crate::services::storage::Storage;
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum MessageDisplay {
+    /// Avatars and names on the first message of each group.
+    #[default]
+    Cozy,
+    /// One line per message, no avatars.
+    Compact,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TimeFormat {
+    #[default]
+    TwelveHour,
+    TwentyFourHour,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum DateOrder {
+    #[default]
+    MonthDayYear,
+    DayMonthYear,
+    YearMonthDay,
+}
+
+/// Skin tone applied to emoji that support one.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum SkinTone {
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    pub const ALL: [SkinTone; 6] = [
+        SkinTone::Default,
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub message_display: MessageDisplay,
+    pub time_format: TimeFormat,
+    pub date_order: DateOrder,
+    pub skin_tone: SkinTone,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        Storage::load("settings")
+    }
+
+    pub fn save(&self) {
+        Storage::save("settings", self);
+    }
+
+    /// Time of day, e.g. "3:04 PM" or "15:04".
+    pub fn format_time(&self, time: DateTime<Utc>) -> String {
+        let format = match self.time_format {
+            TimeFormat::TwelveHour => "%-I:%M %p",
+            TimeFormat::TwentyFourHour => "%H:%M",
+        };
+        time.with_timezone(&Local).format(format).to_string()
+    }
+
+    /// Numeric date, e.g. "03/05/2024".
+    pub fn format_date(&self, time: DateTime<Utc>) -> String {
+        let format = match self.date_order {
+            DateOrder::MonthDayYear => "%m/%d/%Y",
+            DateOrder::DayMonthYear => "%d/%m/%Y",
+            DateOrder::YearMonthDay => "%Y-%m-%d",
+        };
+        time.with_timezone(&Local).format(format).to_string()
+    }
+
+    /// Written out date, e.g. "March 5, 2024".
+    pub fn format_long_date(&self, time: DateTime<Utc>) -> String {
+        let format = match self.date_order {
+            DateOrder::MonthDayYear => "%B %-d, %Y",
+            DateOrder::DayMonthYear => "%-d %B %Y",
+            DateOrder::YearMonthDay => "%Y %B %-d",
+        };
+        time.with_timezone(&Local).format(format).to_string()
+    }
+
+    /// Message header timestamp, relative for today and yesterday.
+    pub fn format_timestamp(&self, time: DateTime<Utc>) -> String {
+        let msg_date = time.with_timezone(&Local).date_naive();
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap_or(today);
+        let time_str = self.format_time(time);
+
+        if msg_date == today {
+            format!("Today at {}", time_str)
+        } else if msg_date == yesterday {
+            format!("Yesterday at {}", time_str)
+        } else {
+            format!("{} {}", self.format_date(time), time_str)
+        }
+    }
+
+    /// Full date and time for tooltips, e.g. "Tuesday, March 5, 2024 3:04 PM".
+    pub fn format_full(&self, time: DateTime<Utc>) -> String {
+        format!(
+            "{}, {} {}",
+            time.with_timezone(&Local).format("%A"),
+            self.format_long_date(time),
+            self.format_time(time)
+        )
+    }
+}
@@ -1,5 +1,10 @@
+use chrono::{DateTime, Utc};
 use tokio::runtime::Handle;
 use std::sync::OnceLock;
+use twilight_model::id::Id;
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+use twilight_model::user::User;
+use twilight_model::util::{ImageHash, Timestamp};
 
 static RUNTIME_HANDLE: OnceLock<Handle> = OnceLock::new();
 
@@ -27,3 +32,122 @@ pub fn get_runtime_handle() -> &'static Handle {
     RUNTIME_HANDLE.get().expect("Tokio runtime not initialized. Call init_runtime() first.")
 }
 
+
+/// Name shown for a user: guild nickname, then global display name, then username.
+pub fn display_name(nick: Option<&String>, user: &User) -> String {
+    nick.or(user.global_name.as_ref())
+        .unwrap_or(&user.name)
+        .clone()
+}
+
+pub fn avatar_url(user_id: Id<UserMarker>, hash: ImageHash) -> String {
+    format!(
+        "https://cdn.discordapp.com/avatars/{}/{}.png",
+        user_id,
+        hash
+    )
+}
+
+pub fn guild_avatar_url(guild_id: Id<GuildMarker>, user_id: Id<UserMarker>, hash: ImageHash) -> String {
+    format!(
+        "https://cdn.discordapp.com/guilds/{}/users/{}/avatars/{}.png",
+        guild_id,
+        user_id,
+        hash
+    )
+}
+
+pub fn banner_url(user_id: Id<UserMarker>, hash: ImageHash) -> String {
+    format!(
+        "https://cdn.discordapp.com/banners/{}/{}.png?size=600",
+        user_id,
+        hash
+    )
+}
+
+/// Discord epoch (2015-01-01) in milliseconds, used to decode snowflake timestamps.
+const DISCORD_EPOCH: i64 = 1_420_070_400_000;
+
+pub fn snowflake_time(snowflake: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis((snowflake >> 22) as i64 + DISCORD_EPOCH)
+}
+
+pub fn to_datetime(timestamp: Timestamp) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(timestamp.as_micros()).unwrap_or_default()
+}
+
+/// Splits `text` into chunks of at most `limit` characters, preferring to break
+/// after a newline, then after a space, and only cutting words when it has to.
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.chars().count() > limit {
+        // Byte offset just past the `limit`th character
+        let end = rest.char_indices().nth(limit).map(|(i, _)| i).unwrap_or(rest.len());
+        // A break right after the last character that fits still counts
+        let window = match rest[end..].chars().next() {
+            Some(c @ ('\n' | ' ')) => &rest[..end + c.len_utf8()],
+            _ => &rest[..end],
+        };
+        let split_at = window.rfind('\n')
+            .or_else(|| window.rfind(' '))
+            .map(|i| i + 1)
+            .unwrap_or(end);
+
+        let chunk = rest[..split_at].trim_end();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        rest = &rest[split_at..];
+    }
+    if !rest.trim().is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_one_chunk() {
+        assert_eq!(split_message("hello world", 11), ["hello world"]);
+        assert!(split_message("", 10).is_empty());
+    }
+
+    #[test]
+    fn prefers_newlines_then_spaces() {
+        assert_eq!(split_message("one two\nthree four", 12), ["one two", "three four"]);
+        assert_eq!(split_message("one two three four", 12), ["one two", "three four"]);
+    }
+
+    #[test]
+    fn breaks_at_the_limit() {
+        // The space right after the limit is a break, not part of either chunk
+        assert_eq!(split_message("hello world again", 11), ["hello world", "again"]);
+        assert_eq!(split_message("hello\nworld", 5), ["hello", "world"]);
+    }
+
+    #[test]
+    fn cuts_words_longer_than_the_limit() {
+        assert_eq!(split_message("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(split_message("aaaa bbbb", 4), ["aaaa", "bbbb"]);
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(split_message("ééééé", 2), ["éé", "éé", "é"]);
+        assert_eq!(split_message("héllo wörld", 7), ["héllo", "wörld"]);
+        assert_eq!(split_message("👍👍👍", 2), ["👍👍", "👍"]);
+    }
+
+    #[test]
+    fn chunks_stay_within_the_limit() {
+        let text = "lorem ipsum dolor sit amet, ünïcödé\n".repeat(200);
+        let chunks = split_message(&text, 2000);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 2000));
+        assert_eq!(chunks.join(" ").split_whitespace().count(), text.split_whitespace().count());
+    }
+}
@@ -0,0 +1,375 @@
+use gpui::{
+    Context, IntoElement, ParentElement, Styled, div, px, prelude::*, img, InteractiveElement, SharedUri,
+};
+use gpui_component::avatar::Avatar;
+use gpui_component::Sizable;
+use twilight_model::id::marker::{CommandMarker, GuildMarker};
+use twilight_model::id::Id;
+use crate::app::AppState;
+use crate::views::channel_messages::ChannelView;
+
+/// How many suggestions the popup shows.
+const MAX_SUGGESTIONS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Trigger {
+    Member,
+    Channel,
+    Emoji,
+    Command,
+}
+
+impl Trigger {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '@' => Some(Trigger::Member),
+            '#' => Some(Trigger::Channel),
+            ':' => Some(Trigger::Emoji),
+            '/' => Some(Trigger::Command),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Trigger::Member => "MEMBERS",
+            Trigger::Channel => "TEXT CHANNELS",
+            Trigger::Emoji => "EMOJI",
+            Trigger::Command => "COMMANDS",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Suggestion {
+    Member {
+        display_name: String,
+        username: String,
+        avatar_url: Option<String>,
+        markup: String,
+    },
+    Channel {
+        name: String,
+        markup: String,
+    },
+    Emoji {
+        shortcode: String,
+        /// The glyph for Unicode emoji, `<:name:id>` for custom ones.
+        markup: String,
+        image_url: Option<String>,
+    },
+    Command {
+        id: Id<CommandMarker>,
+        name: String,
+        description: String,
+        application_name: String,
+    },
+}
+
+impl Suggestion {
+    /// Readable text put into the composer.
+    pub fn display_text(&self) -> String {
+        match self {
+            Suggestion::Member { display_name, .. } => format!("@{}", display_name),
+            Suggestion::Channel { name, .. } => format!("#{}", name),
+            Suggestion::Emoji { shortcode, markup, image_url } => match image_url {
+                Some(_) => format!(":{}:", shortcode),
+                None => markup.clone(),
+            },
+            Suggestion::Command { name, .. } => format!("/{}", name),
+        }
+    }
+
+    /// What the readable text turns into when the message is sent, if it differs.
+    pub fn markup(&self) -> Option<String> {
+        match self {
+            Suggestion::Member { markup, .. } | Suggestion::Channel { markup, .. } => Some(markup.clone()),
+            Suggestion::Emoji { markup, image_url: Some(_), .. } => Some(markup.clone()),
+            Suggestion::Emoji { .. } | Suggestion::Command { .. } => None,
+        }
+    }
+}
+
+/// An open autocomplete popup.
+pub struct Autocomplete {
+    pub trigger: Trigger,
+    /// Byte range of the trigger and query in the composer text.
+    pub range: std::ops::Range<usize>,
+    pub suggestions: Vec<Suggestion>,
+    pub selected: usize,
+}
+
+impl Autocomplete {
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.suggestions.len();
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = (self.selected + self.suggestions.len() - 1) % self.suggestions.len();
+    }
+}
+
+/// Finds the word being typed at `cursor` if it starts with a trigger character.
+/// Slash commands only count at the very start of the message.
+pub fn find_trigger(text: &str, cursor: usize) -> Option<(Trigger, std::ops::Range<usize>, String)> {
+    let before = text.get(..cursor)?;
+    let start = before.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+    let word = &before[start..];
+    let trigger = Trigger::from_char(word.chars().next()?)?;
+
+    if trigger == Trigger::Command && start != 0 {
+        return None;
+    }
+    // Emoji shortcodes need a couple of characters before they're worth suggesting
+    let query = word[1..].to_string();
+    if trigger == Trigger::Emoji && query.chars().count() < 2 {
+        return None;
+    }
+
+    Some((trigger, start..cursor, query))
+}
+
+fn matches(candidate: &str, query: &str) -> bool {
+    candidate.to_lowercase().contains(query)
+}
+
+/// Suggestions for `query`, best matches (prefix matches) first.
+pub fn suggestions(app: &AppState, guild_id: Option<Id<GuildMarker>>, trigger: Trigger, query: &str) -> Vec<Suggestion> {
+    let query = query.to_lowercase();
+    let mut results: Vec<(bool, Suggestion)> = match trigger {
+        Trigger::Member => guild_id
+            .and_then(|guild_id| app.members.get(&guild_id))
+            .map(|members| {
+                members.values()
+                    .filter(|member| matches(&member.display_name, &query) || matches(&member.username, &query))
+                    .map(|member| {
+                        let prefix = member.display_name.to_lowercase().starts_with(&query)
+                            || member.username.to_lowercase().starts_with(&query);
+                        (prefix, Suggestion::Member {
+                            display_name: member.display_name.clone(),
+                            username: member.username.clone(),
+                            avatar_url: member.avatar_url.clone(),
+                            markup: format!("<@{}>", member.user_id),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Trigger::Channel => app.channels
+            .iter()
+            .filter(|channel| matches(&channel.name, &query))
+            .map(|channel| {
+                (channel.name.to_lowercase().starts_with(&query), Suggestion::Channel {
+                    name: channel.name.clone(),
+                    markup: format!("<#{}>", channel.id),
+                })
+            })
+            .collect(),
+        Trigger::Emoji => {
+            let custom = guild_id
+                .and_then(|guild_id| app.emojis.get(&guild_id))
+                .into_iter()
+                .flatten()
+                .filter(|emoji| matches(&emoji.name, &query))
+                .map(|emoji| {
+                    (emoji.name.to_lowercase().starts_with(&query), Suggestion::Emoji {
+                        shortcode: emoji.name.clone(),
+                        markup: emoji.markup(),
+                        image_url: Some(emoji.url()),
+                    })
+                });
+            let unicode = emojis::iter()
+                .filter_map(|emoji| {
+                    let shortcode = emoji.shortcodes().find(|code| code.contains(query.as_str()))?;
+                    Some((shortcode.starts_with(&query), Suggestion::Emoji {
+                        shortcode: shortcode.to_string(),
+                        markup: emoji.as_str().to_string(),
+                        image_url: None,
+                    }))
+                });
+            custom.chain(unicode).collect()
+        }
+        Trigger::Command => app.commands
+            .iter()
+            .filter(|command| command.name.starts_with(&query))
+            .map(|command| {
+                (true, Suggestion::Command {
+                    id: command.id,
+                    name: command.name.clone(),
+                    description: command.description.clone(),
+                    application_name: command.application_name.clone(),
+                })
+            })
+            .collect(),
+    };
+
+    // Stable sort keeps the source order within each group
+    results.sort_by_key(|(prefix, _)| !prefix);
+    results.into_iter().take(MAX_SUGGESTIONS).map(|(_, suggestion)| suggestion).collect()
+}
+
+fn render_suggestion(suggestion: &Suggestion) -> impl IntoElement {
+    let row = div().flex().items_center().gap_2().min_w(px(0.));
+
+    match suggestion {
+        Suggestion::Member { display_name, username, avatar_url, .. } => {
+            let avatar = match avatar_url {
+                Some(url) => Avatar::new().src(url.as_str()).with_size(px(20.)),
+                None => Avatar::new().name(display_name).with_size(px(20.)),
+            };
+            row.child(avatar)
+                .child(div().text_color(gpui::rgb(0xf2f3f5)).child(display_name.clone()))
+                .child(div().text_xs().text_color(gpui::rgb(0x949ba4)).child(username.clone()))
+        }
+        Suggestion::Channel { name, .. } => row
+            .child(div().text_color(gpui::rgb(0x949ba4)).child("#"))
+            .child(div().text_color(gpui::rgb(0xf2f3f5)).child(name.clone())),
+        Suggestion::Emoji { shortcode, markup, image_url } => row
+            .child(match image_url {
+                Some(url) => div().size(px(20.)).child(img(SharedUri::from(url.clone())).size_full()),
+                None => div().size(px(20.)).child(markup.clone()),
+            })
+            .child(div().text_color(gpui::rgb(0xf2f3f5)).child(format!(":{}:", shortcode))),
+        Suggestion::Command { name, description, application_name, .. } => row
+            .child(div().text_color(gpui::rgb(0xf2f3f5)).child(format!("/{}", name)))
+            .child(div().text_xs().text_color(gpui::rgb(0x949ba4)).child(description.clone()))
+            .child(div().text_xs().text_color(gpui::rgb(0x6d6f78)).child(application_name.clone())),
+    }
+}
+
+/// The popup above the composer. Clicking a row accepts it.
+pub fn render_autocomplete(autocomplete: &Autocomplete, cx: &mut Context<ChannelView>) -> impl IntoElement + use<> {
+    div()
+        .flex()
+        .flex_col()
+        .mb_1()
+        .p_1()
+        .rounded_md()
+        .bg(gpui::rgb(0x2b2d31))
+        .child(
+            div()
+                .px_2()
+                .py_1()
+                .text_xs()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(gpui::rgb(0xb5bac1))
+                .child(autocomplete.trigger.title())
+        )
+        .children(autocomplete.suggestions.iter().enumerate().map(|(index, suggestion)| {
+            let is_selected = index == autocomplete.selected;
+            div()
+                .id(("autocomplete", index))
+                .px_2()
+                .py_1()
+                .rounded_sm()
+                .cursor_pointer()
+                .when(is_selected, |this| this.bg(gpui::rgb(0x404249)))
+                .hover(|s| s.bg(gpui::rgb(0x35373c)))
+                .child(render_suggestion(suggestion))
+                .on_click(cx.listener(move |view, _, window, cx| {
+                    view.accept_suggestion_at(index, window, cx);
+                }))
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ChannelInfo, CommandInfo, MemberInfo};
+
+    #[test]
+    fn finds_trigger_at_cursor() {
+        let cases = [
+            ("hi @sa", 6, Some((Trigger::Member, 3..6, "sa"))),
+            ("@", 1, Some((Trigger::Member, 0..1, ""))),
+            ("see #gen", 8, Some((Trigger::Channel, 4..8, "gen"))),
+            ("nice :th", 8, Some((Trigger::Emoji, 5..8, "th"))),
+            ("/ban", 4, Some((Trigger::Command, 0..4, "ban"))),
+            // Cursor in the middle of the text only looks at what's before it
+            ("@sam rest", 4, Some((Trigger::Member, 0..4, "sam"))),
+            ("@sam rest", 9, None),
+            ("hi @sam ", 8, None),
+            ("email@host", 10, None),
+            ("say /ban", 8, None),
+            ("nice :t", 7, None),
+            ("", 0, None),
+        ];
+        for (text, cursor, expected) in cases {
+            let expected = expected.map(|(trigger, range, query)| (trigger, range, query.to_string()));
+            assert_eq!(find_trigger(text, cursor), expected, "{:?} at {}", text, cursor);
+        }
+    }
+
+    #[test]
+    fn cursor_inside_a_char_finds_nothing() {
+        assert_eq!(find_trigger("@é", 2), None);
+    }
+
+    fn names(suggestions: &[Suggestion]) -> Vec<String> {
+        suggestions.iter().map(Suggestion::display_text).collect()
+    }
+
+    fn app() -> AppState {
+        let guild_id = Id::new(1);
+        let mut app = AppState::new();
+        let members = [(10, "samwise", "Sam"), (11, "rosie", "Rosie Samson"), (12, "frodo", "Frodo")];
+        app.members.insert(guild_id, members.into_iter().map(|(id, username, display_name)| {
+            (Id::new(id), MemberInfo {
+                user_id: Id::new(id),
+                username: username.to_string(),
+                display_name: display_name.to_string(),
+                avatar_url: None,
+                roles: Vec::new(),
+                bot: false,
+                joined_at: None,
+            })
+        }).collect());
+        app.channels = [(20, "off-topic"), (21, "general"), (22, "general-2")]
+            .into_iter()
+            .map(|(id, name)| ChannelInfo { id: Id::new(id), name: name.to_string() })
+            .collect();
+        app.commands = [(30, "ban"), (31, "play"), (32, "balance")]
+            .into_iter()
+            .map(|(id, name)| CommandInfo {
+                id: Id::new(id),
+                guild_id: None,
+                application_name: "Bot".to_string(),
+                name: name.to_string(),
+                description: String::new(),
+                definition: serde_json::Value::Null,
+            })
+            .collect();
+        app
+    }
+
+    #[test]
+    fn prefix_matches_come_first() {
+        let app = app();
+        let guild_id = Some(Id::new(1));
+
+        let members = names(&suggestions(&app, guild_id, Trigger::Member, "SAM"));
+        assert_eq!(members, ["@Sam", "@Rosie Samson"]);
+
+        let channels = names(&suggestions(&app, guild_id, Trigger::Channel, "gen"));
+        assert_eq!(channels, ["#general", "#general-2"]);
+        let channels = names(&suggestions(&app, guild_id, Trigger::Channel, "top"));
+        assert_eq!(channels, ["#off-topic"]);
+
+        let commands = names(&suggestions(&app, guild_id, Trigger::Command, "ba"));
+        assert_eq!(commands, ["/ban", "/balance"]);
+    }
+
+    #[test]
+    fn members_need_a_guild() {
+        assert!(suggestions(&app(), None, Trigger::Member, "sam").is_empty());
+    }
+
+    #[test]
+    fn unicode_emoji_and_limit() {
+        let results = suggestions(&app(), None, Trigger::Emoji, "thumbsup");
+        assert_eq!(results.first().map(Suggestion::display_text).as_deref(), Some("👍"));
+        assert_eq!(results[0].markup(), None);
+
+        assert_eq!(suggestions(&app(), None, Trigger::Emoji, "face").len(), MAX_SUGGESTIONS);
+    }
+}
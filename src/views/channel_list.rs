@@ -1,42 +1,112 @@
 use gpui::{
-    Context, IntoElement, ParentElement, Render, Styled, Window, div, Pixels, px,
+    Context, IntoElement, ParentElement, Render, Styled, Window, div, Pixels, px, prelude::*,
+    anchored, deferred, ClickEvent, Entity, Point, Subscription,
 };
 use gpui_component::label::Label;
 use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::tooltip::Tooltip;
+use gpui_component::Sizable;
 use gpui::InteractiveElement;
 use gpui_component::scroll::ScrollableElement;
 use std::sync::{Arc, Mutex};
 use crate::app::AppState;
 use crate::services::discord::DiscordService;
+use crate::views::notification_settings::{NotificationScope, NotificationSettingsView};
+
+struct NotificationPopover {
+    view: Entity<NotificationSettingsView>,
+    position: Point<Pixels>,
+    _subscription: Subscription,
+}
 
 pub struct ChannelsView {
     app: Arc<Mutex<AppState>>,
+    notification_settings: Option<NotificationPopover>,
 }
 
 impl ChannelsView {
     pub fn new(app: Arc<Mutex<AppState>>, _cx: &mut Context<Self>) -> Self {
         Self {
             app,
+            notification_settings: None,
         }
     }
 
-    fn get_channels(&self) -> Vec<crate::app::ChannelInfo> {
+    /// Channels with their unread flag, mention count, whether they are muted and
+    /// whether they have a draft.
+    fn get_channels(&self) -> Vec<(crate::app::ChannelInfo, bool, u32, bool, bool)> {
         self.app.lock()
-            .map(|app| app.channels.clone())
+            .map(|app| {
+                app.channels.iter()
+                    .map(|ch| {
+                        let is_muted = app.is_channel_muted(ch.id);
+                        let is_unread = !is_muted && app.is_channel_unread(ch.id);
+                        (ch.clone(), is_unread, app.channel_mention_count(ch.id), is_muted, app.has_draft(ch.id))
+                    })
+                    .collect()
+            })
             .unwrap_or_default()
     }
 
+    fn open_notification_settings(&mut self, scope: NotificationScope, event: &ClickEvent, cx: &mut Context<Self>) {
+        let app = self.app.clone();
+        let view = cx.new(|cx| NotificationSettingsView::new(app, scope, cx));
+        let subscription = cx.subscribe(&view, |this, _, _: &gpui::DismissEvent, cx| {
+            this.notification_settings = None;
+            cx.notify();
+        });
+        self.notification_settings = Some(NotificationPopover {
+            view,
+            position: event.position(),
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
     fn get_selected_channel(&self) -> Option<twilight_model::id::Id<twilight_model::id::marker::ChannelMarker>> {
         self.app.lock()
-            .map(|app| app.selected_channel.clone())
+            .map(|app| app.selected_channel)
             .unwrap_or(None)
     }
 }
 
+pub fn render_mention_badge(count: u32) -> impl IntoElement {
+    div()
+        .flex_shrink_0()
+        .min_w(px(16.))
+        .h(px(16.))
+        .px_1()
+        .flex()
+        .items_center()
+        .justify_center()
+        .rounded_full()
+        .bg(gpui::rgb(0xf23f43))
+        .text_xs()
+        .font_weight(gpui::FontWeight::BOLD)
+        .text_color(gpui::rgb(0xffffff))
+        .child(count.to_string())
+}
+
 impl Render for ChannelsView {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let channels = self.get_channels();
         let selected = self.get_selected_channel();
+        let any_unread = channels.iter().any(|(_, unread, _, _, _)| *unread);
+        let guild_id = self.app.lock().ok().and_then(|app| app.selected_guild);
+
+        let popover_el = self.notification_settings.as_ref().map(|popover| {
+            let position = popover.position;
+            let view_el = popover.view.update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                anchored()
+                    .position(position)
+                    .snap_to_window_with_margin(px(8.))
+                    .child(view_el)
+            )
+            .with_priority(1)
+        });
 
         div()
             .flex()
@@ -45,9 +115,40 @@ impl Render for ChannelsView {
             .h_full()
             .child(
                 div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
                     .p_4()
                     .border_b_1()
                     .child(Label::new("Channels"))
+                    .when_some(guild_id, |this, guild_id| {
+                        this.child(
+                            Button::new("guild-notifications")
+                                .ghost()
+                                .xsmall()
+                                .tooltip("Notification settings")
+                                .child("🔔")
+                                .on_click(cx.listener(move |view, event, _, cx| {
+                                    view.open_notification_settings(NotificationScope::Guild(guild_id), event, cx);
+                                }))
+                        )
+                    })
+                    .when(any_unread, |this| {
+                        this.child(
+                            Button::new("mark-guild-read")
+                                .ghost()
+                                .xsmall()
+                                .child("Mark as read")
+                                .on_click(cx.listener(|view, _, _, cx| {
+                                    if let Ok(mut app) = view.app.lock()
+                                        && let Some(guild_id) = app.selected_guild
+                                    {
+                                        app.mark_guild_read(guild_id);
+                                    }
+                                    cx.notify();
+                                }))
+                        )
+                    })
             )
             .child(
                 div()
@@ -59,7 +160,7 @@ impl Render for ChannelsView {
                     .min_h(px(0.))
                     .p_2()
                     .children(
-                        channels.into_iter().map(|channel| {
+                        channels.into_iter().map(|(channel, is_unread, mention_count, is_muted, has_draft)| {
                             let channel_id = channel.id;
                             let channel_name = channel.name.clone();
                             let is_selected = selected.as_ref().map(|id| *id == channel_id).unwrap_or(false);
@@ -82,12 +183,78 @@ impl Render for ChannelsView {
                                 button = button.primary();
                             }
 
-                            button.child(
-                                div()
-                                    .child(format!("# {}", channel_name))
-                            )
+                            let app_clone = self.app.clone();
+
+                            div()
+                                .flex()
+                                .items_center()
+                                .group("channel-row")
+                                .child(
+                                    button.child(
+                                        div()
+                                            .when(is_muted, |this| this.text_color(gpui::rgb(0x5c5f66)))
+                                            .when(is_unread, |this| {
+                                                this.font_weight(gpui::FontWeight::BOLD)
+                                                    .text_color(gpui::rgb(0xf2f3f5))
+                                            })
+                                            .child(format!("# {}", channel_name))
+                                    )
+                                )
+                                .when(has_draft && !is_selected, |this| {
+                                    this.child(
+                                        div()
+                                            .id(("channel-draft", channel_id_val))
+                                            .flex_shrink_0()
+                                            .px_1()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x949ba4))
+                                            .tooltip(|window, cx| Tooltip::new("Unsent draft").build(window, cx))
+                                            .child("✏")
+                                    )
+                                })
+                                .when(mention_count > 0, |this| this.child(render_mention_badge(mention_count)))
+                                .child(
+                                    div()
+                                        .invisible()
+                                        .group_hover("channel-row", |s| s.visible())
+                                        .child(
+                                            Button::new(("channel-notifications", channel_id_val))
+                                                .ghost()
+                                                .xsmall()
+                                                .tooltip("Notification settings")
+                                                .child(if is_muted { "🔕" } else { "🔔" })
+                                                .on_click(cx.listener(move |view, event, _, cx| {
+                                                    view.open_notification_settings(
+                                                        NotificationScope::Channel(channel_id),
+                                                        event,
+                                                        cx,
+                                                    );
+                                                }))
+                                        )
+                                )
+                                .when(is_unread, |this| {
+                                    this.child(
+                                        div()
+                                            .invisible()
+                                            .group_hover("channel-row", |s| s.visible())
+                                            .child(
+                                                Button::new(("mark-channel-read", channel_id_val))
+                                                    .ghost()
+                                                    .xsmall()
+                                                    .tooltip("Mark as read")
+                                                    .child("✓")
+                                                    .on_click(cx.listener(move |_view, _, _, cx| {
+                                                        if let Ok(mut app) = app_clone.lock() {
+                                                            app.mark_channel_read(channel_id);
+                                                        }
+                                                        cx.notify();
+                                                    }))
+                                            )
+                                    )
+                                })
                         })
                     )
             )
+            .children(popover_el)
     }
 }
@@ -1,79 +1,711 @@
 use gpui::{
     Context, IntoElement, ParentElement, Render, Styled, Window, div, px,
     prelude::*, img, ObjectFit, InteractiveElement, SharedUri,
+    anchored, deferred, actions, EntityInputHandler, App, ClickEvent, DismissEvent, KeyBinding, Pixels, Point, ScrollHandle,
 };
 use gpui_component::input::{Input, InputState, InputEvent};
 use gpui_component::scroll::ScrollableElement;
-use chrono::{Local, TimeZone};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use gpui_component::label::Label;
 use gpui_component::avatar::Avatar;
 use gpui_component::Sizable;
 use gpui_component::skeleton::Skeleton;
+use gpui_component::tooltip::Tooltip;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::notification::Notification as ToastNotification;
+use gpui_component::{Root, WindowExt};
 use std::sync::{Arc, Mutex};
-use crate::app::{AppState, MessageInfo, AttachmentInfo};
+use std::time::{Duration, Instant};
+use crate::app::{expand_tokens, shift_tokens, AppState, CommandInfo, ComposerToken, MessageInfo, AttachmentInfo, EmojiInfo, InteractionResponseState, PickedEmoji};
+use crate::settings::{MessageDisplay, Settings};
+use twilight_model::id::Id;
+use twilight_model::id::marker::{ChannelMarker, MessageMarker};
+use crate::services::discord::{DiscordService, MAX_MESSAGE_LEN};
+use crate::services::gateway::GatewayService;
+use crate::utils::split_message;
+use crate::views::autocomplete::{self, find_trigger, render_autocomplete, Autocomplete, Suggestion, Trigger};
+use crate::views::components::render_components;
 use crate::views::channel_list::ChannelsView;
+use crate::views::member_list::MemberListView;
+use crate::views::mentions::MentionsView;
+use crate::views::dev_console::DevConsoleView;
+use crate::views::emoji_picker::{EmojiPickerEvent, EmojiPickerView};
+use crate::views::interaction_inbox::InteractionInboxView;
+use crate::views::profile::ProfileView;
+use crate::views::rate_limits::RateLimitView;
+use crate::views::settings::SettingsView;
+use crate::views::status_menu::StatusMenuView;
 use crate::views::server_list::ServerListView;
 
+struct ProfilePopout {
+    view: gpui::Entity<ProfileView>,
+    position: Point<Pixels>,
+    _subscription: gpui::Subscription,
+}
+
+/// Where a picked emoji goes.
+#[derive(Clone, Copy)]
+enum EmojiTarget {
+    Composer,
+    Reaction(Id<MessageMarker>),
+}
+
+struct EmojiPickerPopout {
+    view: gpui::Entity<EmojiPickerView>,
+    position: Point<Pixels>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+struct StatusMenuPopout {
+    view: gpui::Entity<StatusMenuView>,
+    position: Point<Pixels>,
+    _subscription: gpui::Subscription,
+}
+
 pub struct ChannelView {
     app: Arc<Mutex<AppState>>,
     channels_view: Option<gpui::Entity<ChannelsView>>,
     server_list_view: Option<gpui::Entity<ServerListView>>,
+    member_list_view: Option<gpui::Entity<MemberListView>>,
+    profile: Option<ProfilePopout>,
+    emoji_picker: Option<EmojiPickerPopout>,
+    status_menu: Option<StatusMenuPopout>,
+    settings: Option<(gpui::Entity<SettingsView>, gpui::Subscription)>,
+    mentions: Option<(gpui::Entity<MentionsView>, gpui::Subscription)>,
+    console: Option<(gpui::Entity<DevConsoleView>, gpui::Subscription)>,
+    interaction_inbox: Option<(gpui::Entity<InteractionInboxView>, gpui::Subscription)>,
+    rate_limits: Option<(gpui::Entity<RateLimitView>, gpui::Subscription)>,
+    messages_scroll: ScrollHandle,
     input: gpui::Entity<InputState>,
     _subscription: gpui::Subscription,
+    _activation_subscription: gpui::Subscription,
+    last_typing_trigger: Option<(Id<ChannelMarker>, Instant)>,
+    /// Text over the length limit, waiting for the user to split or upload it.
+    long_message: Option<String>,
+    autocomplete: Option<Autocomplete>,
+    /// Autocomplete text in the composer, expanded to markup when the message is sent.
+    composer_tokens: Vec<ComposerToken>,
+    /// Composer text as of the last change, to move the tokens along with edits.
+    composer_text: String,
+    /// Channel the composer currently holds the draft for.
+    draft_channel: Option<Id<ChannelMarker>>,
+    _refresh_task: gpui::Task<()>,
 }
 
 impl ChannelView {
     pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, cx: &mut Context<Self>) -> Self {
-        let input = cx.new(|cx| InputState::new(window, cx).placeholder("Message #general"));
+        let input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .auto_grow(1, COMPOSER_MAX_ROWS)
+                .placeholder("Message #general")
+        });
         
-        let subscription = cx.subscribe_in(&input, window, |view, _input, event, window, cx| {
-             if let InputEvent::PressEnter { .. } = event {
-                 let text = view.input.read(cx).value().to_string();
-                 if !text.is_empty() {
-                    let (client, channel_id) = {
-                        let guard = view.app.lock().unwrap();
-                        (guard.http_client.clone(), guard.selected_channel)
-                    };
-
-                    if let (Some(client), Some(channel_id)) = (client, channel_id) {
-                        let text_clone = text.clone();
-                        crate::utils::get_runtime_handle().spawn(async move {
-                            if let Err(e) = client.create_message(channel_id).content(&text_clone).await {
-                                eprintln!("Failed to send message: {}", e);
-                            }
-                        });
-                    }
-                    view.input.update(cx, |state, cx| state.set_value("", window, cx));
+        let subscription = cx.subscribe_in(&input, window, |view, _input, event, _window, cx| {
+             if let InputEvent::Change = event {
+                 // Drafts and autocomplete also set the text, and that isn't typing
+                 let typed = view.track_composer_edit(cx);
+                 if typed && !view.input.read(cx).value().is_empty() {
+                     view.send_typing();
                  }
+                 view.update_autocomplete(cx);
+                 view.stash_draft(cx);
              }
         });
 
+        // Notification backends pick toasts or desktop notifications based on focus.
+        let activation_subscription = cx.observe_window_activation(window, |view, window, _cx| {
+            if let Ok(mut app) = view.app.lock() {
+                app.window_active = window.is_window_active();
+            }
+        });
+
+        // Gateway events mutate the shared state from the tokio runtime, so poll the
+        // revision counter and re-render when it moves.
+        let app_clone = app.clone();
+        let refresh_task = cx.spawn_in(window, async move |this, cx| {
+            let mut last_revision = 0;
+            let mut last_selected = None;
+            loop {
+                cx.background_executor().timer(Duration::from_millis(100)).await;
+                let (revision, selected, has_toasts) = app_clone.lock()
+                    .map(|mut app| {
+                        // Typing indicators expire without an event, so prune them here
+                        if app.prune_typing() {
+                            app.revision += 1;
+                        }
+                        let has_toasts = app.toasts.lock().is_ok_and(|toasts| !toasts.is_empty());
+                        (app.revision, app.selected_channel, has_toasts)
+                    })
+                    .unwrap_or((last_revision, last_selected, false));
+                if has_toasts && this.update_in(cx, |view, window, cx| view.show_toasts(window, cx)).is_err() {
+                    break;
+                }
+                if selected != last_selected {
+                    last_selected = selected;
+                    if this.update_in(cx, |view, window, cx| view.sync_draft(window, cx)).is_err() {
+                        break;
+                    }
+                }
+                if revision != last_revision {
+                    last_revision = revision;
+                    if this.update(cx, |_, cx| cx.notify()).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
         Self {
             app,
             channels_view: None,
             server_list_view: None,
+            member_list_view: None,
+            profile: None,
+            emoji_picker: None,
+            status_menu: None,
+            settings: None,
+            mentions: None,
+            console: None,
+            interaction_inbox: None,
+            rate_limits: None,
+            messages_scroll: ScrollHandle::new(),
             input,
             _subscription: subscription,
+            _activation_subscription: activation_subscription,
+            last_typing_trigger: None,
+            long_message: None,
+            autocomplete: None,
+            composer_tokens: Vec::new(),
+            composer_text: String::new(),
+            draft_channel: None,
+            _refresh_task: refresh_task,
+        }
+    }
+
+    fn send_message(&mut self, _: &SendMessage, window: &mut Window, cx: &mut Context<Self>) {
+        let text = expand_tokens(self.input.read(cx).value().as_ref(), &self.composer_tokens);
+        if text.trim().is_empty() {
+            return;
+        }
+        let Some(channel_id) = self.app.lock().ok().and_then(|app| app.selected_channel) else {
+            return;
+        };
+
+        if text.chars().count() > MAX_MESSAGE_LEN {
+            self.long_message = Some(text);
+            cx.notify();
+            return;
+        }
+
+        DiscordService::send_messages(self.app.clone(), channel_id, vec![text]);
+        self.clear_composer(window, cx);
+    }
+
+    /// Copies the composer into the draft for its channel. The write to disk is
+    /// batched, and whatever is still pending is written when the app quits.
+    fn stash_draft(&mut self, cx: &mut Context<Self>) {
+        let Some(channel_id) = self.draft_channel else {
+            return;
+        };
+        let text = self.input.read(cx).value().to_string();
+
+        if let Ok(mut app) = self.app.lock() {
+            let draft = app.drafts.entry(channel_id).or_default();
+            draft.text = text;
+            draft.tokens = self.composer_tokens.clone();
+            if draft.is_empty() {
+                app.drafts.remove(&channel_id);
+            }
+            app.save_drafts();
+        }
+    }
+
+    /// Swaps the composer over to the selected channel's draft. Called by the refresh
+    /// task when the selection changes, since setting the text during render would
+    /// emit input events mid-frame.
+    fn sync_draft(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = self.app.lock().ok().and_then(|app| app.selected_channel);
+        if selected == self.draft_channel {
+            return;
+        }
+
+        self.stash_draft(cx);
+        let draft = self.app.lock()
+            .ok()
+            .and_then(|app| selected.and_then(|id| app.drafts.get(&id).cloned()))
+            .unwrap_or_default();
+
+        self.draft_channel = selected;
+        self.composer_tokens = draft.tokens;
+        self.composer_text = draft.text.clone();
+        self.long_message = None;
+        self.autocomplete = None;
+        self.input.update(cx, |state, cx| state.set_value(draft.text, window, cx));
+    }
+
+    /// Keeps the autocomplete tokens on their text after the composer changed.
+    /// Returns false if the change was already tracked, i.e. made by us.
+    fn track_composer_edit(&mut self, cx: &mut Context<Self>) -> bool {
+        let text = self.input.read(cx).value().to_string();
+        if text == self.composer_text {
+            return false;
+        }
+        shift_tokens(&mut self.composer_tokens, &self.composer_text, &text);
+        self.composer_text = text;
+        true
+    }
+
+    /// Re-evaluates the autocomplete popup for the word at the cursor.
+    fn update_autocomplete(&mut self, cx: &mut Context<Self>) {
+        let (text, cursor) = {
+            let input = self.input.read(cx);
+            (input.value().to_string(), input.cursor())
+        };
+
+        self.autocomplete = find_trigger(&text, cursor).and_then(|(trigger, range, query)| {
+            let (guild_id, suggestions) = self.app.lock()
+                .map(|app| {
+                    let suggestions = autocomplete::suggestions(&app, app.selected_guild, trigger, &query);
+                    (app.selected_guild, suggestions)
+                })
+                .ok()?;
+
+            // The member cache may not have everyone, so ask the gateway too
+            if let (Trigger::Member, Some(guild_id)) = (trigger, guild_id)
+                && !query.is_empty() && suggestions.len() < 10
+            {
+                GatewayService::search_members(&self.app, guild_id, &query);
+            }
+
+            let selected = self.autocomplete.as_ref()
+                .filter(|current| current.trigger == trigger && current.range.start == range.start)
+                .map(|current| current.selected.min(suggestions.len().saturating_sub(1)))
+                .unwrap_or(0);
+            (!suggestions.is_empty()).then_some(Autocomplete { trigger, range, suggestions, selected })
+        });
+        cx.notify();
+    }
+
+    fn select_next_suggestion(&mut self, _: &SelectNextSuggestion, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(autocomplete) = self.autocomplete.as_mut() {
+            autocomplete.select_next();
+            cx.notify();
+        }
+    }
+
+    fn select_prev_suggestion(&mut self, _: &SelectPrevSuggestion, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(autocomplete) = self.autocomplete.as_mut() {
+            autocomplete.select_prev();
+            cx.notify();
+        }
+    }
+
+    fn accept_suggestion(&mut self, _: &AcceptSuggestion, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(index) = self.autocomplete.as_ref().map(|autocomplete| autocomplete.selected) {
+            self.accept_suggestion_at(index, window, cx);
+        }
+    }
+
+    fn dismiss_autocomplete(&mut self, _: &DismissAutocomplete, _: &mut Window, cx: &mut Context<Self>) {
+        self.autocomplete = None;
+        cx.notify();
+    }
+
+    /// Replaces the trigger word with the suggestion's readable text.
+    pub fn accept_suggestion_at(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(autocomplete) = self.autocomplete.take() else {
+            return;
+        };
+        let Some(suggestion) = autocomplete.suggestions.get(index) else {
+            return;
+        };
+
+        // Bots can't run commands, so picking one opens it for editing instead
+        if let Suggestion::Command { id, .. } = suggestion {
+            let command = self.app.lock()
+                .ok()
+                .and_then(|app| app.commands.iter().find(|command| command.id == *id).cloned());
+            self.clear_composer(window, cx);
+            self.open_console(command, window, cx);
+            return;
+        }
+
+        let display = suggestion.display_text();
+        let replacement = format!("{} ", display);
+        let range = autocomplete.range;
+        self.input.update(cx, |state, cx| {
+            // The input handler works in UTF-16 offsets
+            let text = state.value().to_string();
+            let start = text[..range.start].encode_utf16().count();
+            let end = start + text[range.clone()].encode_utf16().count();
+            state.replace_text_in_range(Some(start..end), &replacement, window, cx);
+        });
+        self.track_composer_edit(cx);
+        if let Some(markup) = suggestion.markup() {
+            self.composer_tokens.push(ComposerToken { start: range.start, display, markup });
+        }
+        self.autocomplete = None;
+        cx.notify();
+    }
+
+    /// Tokens inserted by autocomplete that are still in the composer, shown as pills.
+    fn render_composer_pills(&self, text: &str) -> Option<impl IntoElement> {
+        let pills: Vec<_> = self.composer_tokens
+            .iter()
+            .filter(|token| token.is_intact(text))
+            .map(|token| token.display.clone())
+            .collect();
+        if pills.is_empty() {
+            return None;
+        }
+
+        Some(
+            div()
+                .flex()
+                .flex_wrap()
+                .gap_1()
+                .pb_1()
+                .children(pills.into_iter().map(|pill| {
+                    div()
+                        .px_1()
+                        .rounded_sm()
+                        .bg(gpui::rgb(0x3c4270))
+                        .text_xs()
+                        .font_weight(gpui::FontWeight::MEDIUM)
+                        .text_color(gpui::rgb(0xc9cdfb))
+                        .child(pill)
+                }))
+        )
+    }
+
+    fn insert_newline(&mut self, _: &InsertNewline, window: &mut Window, cx: &mut Context<Self>) {
+        self.input.update(cx, |state, cx| state.insert("\n", window, cx));
+    }
+
+    /// Sends the pending over-long message, either split up or as a file.
+    fn send_long_message(&mut self, as_file: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = self.long_message.take() else {
+            return;
+        };
+        let Some(channel_id) = self.app.lock().ok().and_then(|app| app.selected_channel) else {
+            return;
+        };
+
+        if as_file {
+            DiscordService::send_as_file(self.app.clone(), channel_id, text);
+        } else {
+            DiscordService::send_messages(self.app.clone(), channel_id, split_message(&text, MAX_MESSAGE_LEN));
+        }
+        self.clear_composer(window, cx);
+    }
+
+    fn clear_composer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.input.update(cx, |state, cx| state.set_value("", window, cx));
+        self.long_message = None;
+        self.autocomplete = None;
+        self.composer_tokens.clear();
+        self.composer_text.clear();
+        if let Ok(mut app) = self.app.lock() {
+            if let Some(channel_id) = self.draft_channel {
+                app.drafts.remove(&channel_id);
+            }
+            app.save_drafts();
+        }
+        // The message ends our typing indicator on Discord's side
+        self.last_typing_trigger = None;
+        cx.notify();
+    }
+
+    fn render_long_message_prompt(&self, text: &str, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let length = text.chars().count();
+        let parts = split_message(text, MAX_MESSAGE_LEN).len();
+
+        div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .gap_2()
+            .mb_1()
+            .p_2()
+            .rounded_md()
+            .bg(gpui::rgb(0x2b2d31))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(gpui::rgb(0xdbdee1))
+                    .child(format!(
+                        "Your message is {} characters, over the {} character limit.",
+                        length, MAX_MESSAGE_LEN
+                    ))
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_shrink_0()
+                    .gap_1()
+                    .child(
+                        Button::new("long-message-split")
+                            .small()
+                            .primary()
+                            .child(format!("Split into {} messages", parts))
+                            .on_click(cx.listener(|view, _, window, cx| view.send_long_message(false, window, cx)))
+                    )
+                    .child(
+                        Button::new("long-message-file")
+                            .small()
+                            .ghost()
+                            .child("Upload as message.txt")
+                            .on_click(cx.listener(|view, _, window, cx| view.send_long_message(true, window, cx)))
+                    )
+                    .child(
+                        Button::new("long-message-cancel")
+                            .small()
+                            .ghost()
+                            .child("Cancel")
+                            .on_click(cx.listener(|view, _, _, cx| {
+                                view.long_message = None;
+                                cx.notify();
+                            }))
+                    )
+            )
+    }
+
+    /// Sends a typing trigger for the selected channel, at most once per
+    /// `TYPING_TRIGGER_INTERVAL`.
+    fn send_typing(&mut self) {
+        let Some(channel_id) = self.app.lock().ok().and_then(|app| app.selected_channel) else {
+            return;
+        };
+        if let Some((last_channel, sent_at)) = self.last_typing_trigger
+            && last_channel == channel_id && sent_at.elapsed() < TYPING_TRIGGER_INTERVAL
+        {
+            return;
+        }
+        self.last_typing_trigger = Some((channel_id, Instant::now()));
+        DiscordService::trigger_typing(self.app.clone(), channel_id);
+    }
+
+    /// Moves toasts queued by the notifier onto the window's notification layer.
+    /// Called by the refresh task, not during render.
+    fn show_toasts(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let toasts: Vec<_> = self.app.lock()
+            .ok()
+            .and_then(|app| app.toasts.lock().map(|mut toasts| toasts.drain(..).collect()).ok())
+            .unwrap_or_default();
+
+        for toast in toasts {
+            let app = self.app.clone();
+            let (channel_id, message_id) = (toast.channel_id, toast.message_id);
+            window.push_notification(
+                ToastNotification::new()
+                    .title(toast.title)
+                    .message(toast.body)
+                    .on_click(move |_, _, _| {
+                        let guild_id = app.lock().ok().and_then(|app| app.channel_guilds.get(&channel_id).copied());
+                        DiscordService::jump_to_message(app.clone(), guild_id, channel_id, message_id);
+                    }),
+                cx,
+            );
         }
     }
 
     fn get_messages(&self) -> Vec<MessageInfo> {
         self.app.lock()
-            .map(|app| app.messages.clone())
+            .map(|app| {
+                let mut messages = app.messages.clone();
+                if let Some(guild_id) = app.selected_guild {
+                    for msg in &mut messages {
+                        app.resolve_author(guild_id, msg);
+                    }
+                }
+                messages
+            })
             .unwrap_or_default()
     }
 
     fn get_channel_name(&self) -> String {
         self.app.lock()
             .map(|app| {
+                let is_selected = |ch: &&crate::app::ChannelInfo| app.selected_channel.map(|id| ch.id == id).unwrap_or(false);
                 app.channels.iter()
-                    .find(|ch| app.selected_channel.map(|id| ch.id == id).unwrap_or(false))
+                    .find(is_selected)
                     .map(|ch| format!("# {}", ch.name.clone()))
+                    .or_else(|| app.dm_channels.iter().find(is_selected).map(|ch| format!("@{}", ch.name.clone())))
                     .unwrap_or_else(|| "Select a channel".to_string())
             })
             .unwrap_or_else(|_| "Select a channel".to_string())
     }
 
+    fn has_selected_channel(&self) -> bool {
+        self.app.lock()
+            .map(|app| app.selected_channel.is_some())
+            .unwrap_or(false)
+    }
+
+    fn toggle_settings(&mut self, cx: &mut Context<Self>) {
+        if self.settings.take().is_none() {
+            let app = self.app.clone();
+            let view = cx.new(|cx| SettingsView::new(app, cx));
+            let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+                this.settings = None;
+                cx.notify();
+            });
+            self.settings = Some((view, subscription));
+        }
+        cx.notify();
+    }
+
+    fn toggle_console(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.console.take().is_none() {
+            self.open_console(None, window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Opens the developer console, with `command` loaded into the editor if given.
+    fn open_console(&mut self, command: Option<CommandInfo>, window: &mut Window, cx: &mut Context<Self>) {
+        let app = self.app.clone();
+        let view = cx.new(|cx| DevConsoleView::new(window, app, command, cx));
+        let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+            this.console = None;
+            cx.notify();
+        });
+        self.console = Some((view, subscription));
+        cx.notify();
+    }
+
+    fn toggle_interaction_inbox(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.interaction_inbox.take().is_none() {
+            let app = self.app.clone();
+            let view = cx.new(|cx| InteractionInboxView::new(window, app, cx));
+            let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+                this.interaction_inbox = None;
+                cx.notify();
+            });
+            self.interaction_inbox = Some((view, subscription));
+        }
+        cx.notify();
+    }
+
+    fn toggle_rate_limits(&mut self, cx: &mut Context<Self>) {
+        if self.rate_limits.take().is_none() {
+            let view = cx.new(RateLimitView::new);
+            let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+                this.rate_limits = None;
+                cx.notify();
+            });
+            self.rate_limits = Some((view, subscription));
+        }
+        cx.notify();
+    }
+
+    fn open_status_menu(&mut self, position: Point<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        let app = self.app.clone();
+        let view = cx.new(|cx| StatusMenuView::new(window, app, cx));
+        let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+            this.status_menu = None;
+            cx.notify();
+        });
+
+        self.status_menu = Some(StatusMenuPopout {
+            view,
+            position,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn toggle_mentions(&mut self, cx: &mut Context<Self>) {
+        if self.mentions.take().is_none() {
+            let app = self.app.clone();
+            let view = cx.new(|cx| MentionsView::new(app, cx));
+            let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+                this.mentions = None;
+                cx.notify();
+            });
+            self.mentions = Some((view, subscription));
+        }
+        cx.notify();
+    }
+
+    fn open_profile(&mut self, msg: &MessageInfo, position: Point<Pixels>, cx: &mut Context<Self>) {
+        let app = self.app.clone();
+        let view = cx.new(|cx| {
+            ProfileView::new(app, msg.author_id, msg.author_name.clone(), msg.author_avatar_url.clone(), cx)
+        });
+        let subscription = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+            this.profile = None;
+            cx.notify();
+        });
+
+        self.profile = Some(ProfilePopout {
+            view,
+            position,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn open_emoji_picker(&mut self, target: EmojiTarget, position: Point<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        let app = self.app.clone();
+        let view = cx.new(|cx| EmojiPickerView::new(window, app, cx));
+        let picked = cx.subscribe_in(&view, window, move |this, _, event: &EmojiPickerEvent, window, cx| {
+            let EmojiPickerEvent::Picked(emoji) = event;
+            match target {
+                EmojiTarget::Composer => this.insert_emoji(emoji, window, cx),
+                EmojiTarget::Reaction(message_id) => {
+                    let channel_id = this.app.lock().ok().and_then(|app| app.selected_channel);
+                    if let Some(channel_id) = channel_id {
+                        DiscordService::add_reaction(this.app.clone(), channel_id, message_id, emoji.clone());
+                    }
+                }
+            }
+            this.emoji_picker = None;
+            cx.notify();
+        });
+        let dismissed = cx.subscribe(&view, |this, _, _: &DismissEvent, cx| {
+            this.emoji_picker = None;
+            cx.notify();
+        });
+
+        self.emoji_picker = Some(EmojiPickerPopout {
+            view,
+            position,
+            _subscriptions: vec![picked, dismissed],
+        });
+        cx.notify();
+    }
+
+    /// Puts a picked emoji into the composer. Custom emoji go in as `:name:` and
+    /// are expanded on send, like autocomplete suggestions.
+    fn insert_emoji(&mut self, emoji: &PickedEmoji, window: &mut Window, cx: &mut Context<Self>) {
+        let (text, markup) = match emoji {
+            PickedEmoji::Unicode(glyph) => (glyph.clone(), None),
+            PickedEmoji::Custom { id, name, animated } => {
+                let markup = EmojiInfo { id: *id, name: name.clone(), animated: *animated }.markup();
+                (format!(":{}:", name), Some(markup))
+            }
+        };
+        let cursor = self.input.update(cx, |state, cx| {
+            state.insert(text.clone(), window, cx);
+            state.focus(window, cx);
+            state.cursor()
+        });
+        self.track_composer_edit(cx);
+        // The cursor ends up right after the inserted text
+        if let (Some(markup), Some(start)) = (markup, cursor.checked_sub(text.len()))
+            && self.composer_text.get(start..cursor) == Some(text.as_str())
+        {
+            self.composer_tokens.push(ComposerToken { start, display: text, markup });
+        }
+    }
+
+    fn is_member_list_shown(&self) -> bool {
+        self.app.lock()
+            .map(|app| app.show_member_list)
+            .unwrap_or(false)
+    }
+
     fn has_selected_guild(&self) -> bool {
         self.app.lock()
             .map(|app| app.selected_guild.is_some())
@@ -83,11 +715,132 @@ impl ChannelView {
     fn render_message_view(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let messages = self.get_messages();
         let channel_name = self.get_channel_name();
+        let composer_text = self.input.read(cx).value().to_string();
+        // Counted as sent, with mentions and custom emoji expanded
+        let length = expand_tokens(&composer_text, &self.composer_tokens).chars().count();
+        let pills_el = self.render_composer_pills(&composer_text);
+        let autocomplete_el = self.autocomplete.as_ref().map(|autocomplete| render_autocomplete(autocomplete, cx));
+        let long_message = self.long_message.clone();
+        let (settings, new_messages_since, jump_to, typing) = self.app.lock()
+            .map(|app| {
+                let typing = app.selected_channel
+                    .map(|channel_id| app.typing_names(channel_id))
+                    .unwrap_or_default();
+                (app.settings.clone(), app.new_messages_since, app.jump_to_message, typing)
+            })
+            .unwrap_or_default();
+        let (message_elements, jump_index) = render_messages(messages, new_messages_since, jump_to, &settings, cx);
+        if let Some(index) = jump_index {
+            self.messages_scroll.scroll_to_item(index);
+            if let Ok(mut app) = self.app.lock() {
+                app.jump_to_message = None;
+            }
+        }
 
         self.input.update(cx, |input, cx| {
              input.set_placeholder(format!("Message {}", channel_name), window, cx);
         });
 
+        let mut members_button = Button::new("toggle-member-list")
+            .small()
+            .child("Members")
+            .on_click(cx.listener(|view, _, _, cx| {
+                if let Ok(mut app) = view.app.lock() {
+                    app.show_member_list = !app.show_member_list;
+                    GatewayService::request_members(&view.app, &mut app);
+                }
+                cx.notify();
+            }));
+        members_button = if self.is_member_list_shown() {
+            members_button.primary()
+        } else {
+            members_button.ghost()
+        };
+
+        let mention_count = self.app.lock()
+            .map(|app| app.mention_counts.values().sum::<u32>())
+            .unwrap_or(0);
+        let mut inbox_button = Button::new("open-mentions")
+            .small()
+            .child(if mention_count > 0 { format!("Inbox ({})", mention_count) } else { "Inbox".to_string() })
+            .on_click(cx.listener(|view, _, _, cx| view.toggle_mentions(cx)));
+        inbox_button = if mention_count > 0 {
+            inbox_button.danger()
+        } else {
+            inbox_button.ghost()
+        };
+
+        let settings_button = Button::new("open-settings")
+            .small()
+            .ghost()
+            .child("Settings")
+            .on_click(cx.listener(|view, _, _, cx| view.toggle_settings(cx)));
+
+        let connection = self.app.lock().map(|app| app.connection.clone()).unwrap_or_default();
+        let connection_indicator = div()
+            .flex()
+            .items_center()
+            .gap_1()
+            .px_2()
+            .text_xs()
+            .text_color(gpui::rgb(0x949ba4))
+            .child(div().size(px(8.)).rounded_full().bg(gpui::rgb(connection.status.color())))
+            .child(connection.summary());
+
+        let presence = self.app.lock().map(|app| app.presence.clone()).unwrap_or_default();
+        let status_button = Button::new("open-status-menu")
+            .small()
+            .ghost()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(div().size(px(8.)).rounded_full().bg(gpui::rgb(presence.status.color())))
+                    .child(presence.summary())
+            )
+            .on_click(cx.listener(|view, event: &ClickEvent, window, cx| {
+                view.open_status_menu(event.position(), window, cx);
+            }));
+
+        // Bots can manage their application's commands and answer interactions by hand
+        let (is_bot, pending_interactions) = self.app.lock()
+            .map(|app| {
+                let pending = app.interactions
+                    .iter()
+                    .filter(|interaction| interaction.state == InteractionResponseState::Pending && interaction.deadline() > Utc::now())
+                    .count();
+                (app.is_bot, pending)
+            })
+            .unwrap_or_default();
+        let console_button = is_bot.then(|| {
+            Button::new("open-console")
+                .small()
+                .ghost()
+                .child("Console")
+                .on_click(cx.listener(|view, _, window, cx| view.toggle_console(window, cx)))
+        });
+        let interactions_button = is_bot.then(|| {
+            let button = Button::new("open-interactions")
+                .small()
+                .child(if pending_interactions > 0 {
+                    format!("Interactions ({})", pending_interactions)
+                } else {
+                    "Interactions".to_string()
+                })
+                .on_click(cx.listener(|view, _, window, cx| view.toggle_interaction_inbox(window, cx)));
+            if pending_interactions > 0 { button.danger() } else { button.ghost() }
+        });
+
+        // Request queue and rate limits, for debugging
+        let rate_limits_button = cfg!(debug_assertions).then(|| {
+            Button::new("open-rate-limits")
+                .small()
+                .ghost()
+                .child("Rate limits")
+                .on_click(cx.listener(|view, _, _, cx| view.toggle_rate_limits(cx)))
+        });
+
         div()
             .flex()
             .flex_col()
@@ -107,6 +860,15 @@ impl ChannelView {
                         Label::new(channel_name)
                             .text_color(gpui::rgb(0xf2f3f5))
                     )
+                    .child(div().flex_1())
+                    .child(connection_indicator)
+                    .child(status_button)
+                    .child(members_button)
+                    .child(inbox_button)
+                    .children(console_button)
+                    .children(interactions_button)
+                    .children(rate_limits_button)
+                    .child(settings_button)
             )
             .child(
                 // Messages area
@@ -115,7 +877,7 @@ impl ChannelView {
                     .relative()
                     .min_h(px(0.))
                     .child(
-                        if messages.is_empty() {
+                        if message_elements.is_empty() {
                             div()
                                 .size_full()
                                 .flex()
@@ -132,27 +894,284 @@ impl ChannelView {
                                 .flex()
                                 .flex_col()
                                 .id("messages-list")
+                                .track_scroll(&self.messages_scroll)
                                 .overflow_y_scrollbar()
                                 .size_full()
                                 .py_4()
-                                .children(
-                                    messages.into_iter().map(|msg| {
-                                        render_message(msg)
-                                    })
-                                )
+                                .children(message_elements)
                                 .into_any_element()
                         }
                     )
             )
+            .children(long_message.map(|text| self.render_long_message_prompt(&text, cx)))
             .child(
                 div()
                     .mb_1()
-                    .child(Input::new(&self.input).h_12())
+                    .child(render_typing_indicator(&typing))
+                    .children(autocomplete_el)
+                    .children(pills_el)
+                    .child(
+                        div()
+                            .key_context(if self.autocomplete.is_some() { AUTOCOMPLETE_CONTEXT } else { COMPOSER_CONTEXT })
+                            .on_action(cx.listener(Self::send_message))
+                            .on_action(cx.listener(Self::insert_newline))
+                            .on_action(cx.listener(Self::select_next_suggestion))
+                            .on_action(cx.listener(Self::select_prev_suggestion))
+                            .on_action(cx.listener(Self::accept_suggestion))
+                            .on_action(cx.listener(Self::dismiss_autocomplete))
+                            .relative()
+                            .flex()
+                            .items_end()
+                            .gap_1()
+                            .child(div().flex_1().min_w(px(0.)).child(Input::new(&self.input)))
+                            .child(
+                                Button::new("composer-emoji")
+                                    .ghost()
+                                    .small()
+                                    .tooltip("Select emoji")
+                                    .child("😀")
+                                    .on_click(cx.listener(|view, event: &ClickEvent, window, cx| {
+                                        view.open_emoji_picker(EmojiTarget::Composer, event.position(), window, cx);
+                                    }))
+                            )
+                            .when(length >= COUNTER_THRESHOLD, |this| {
+                                let remaining = MAX_MESSAGE_LEN as i64 - length as i64;
+                                this.child(
+                                    div()
+                                        .absolute()
+                                        .bottom(px(4.))
+                                        .right(px(44.))
+                                        .text_xs()
+                                        .text_color(if remaining < 0 { gpui::rgb(0xf23f43) } else { gpui::rgb(0xb5bac1) })
+                                        .child(remaining.to_string())
+                                )
+                            })
+                    )
             )
     }
 }
 
-fn render_message(msg: MessageInfo) -> impl IntoElement {
+actions!(
+    composer,
+    [
+        SendMessage,
+        InsertNewline,
+        SelectNextSuggestion,
+        SelectPrevSuggestion,
+        AcceptSuggestion,
+        DismissAutocomplete
+    ]
+);
+
+const COMPOSER_CONTEXT: &str = "Composer";
+const AUTOCOMPLETE_CONTEXT: &str = "Composer Autocomplete";
+const COMPOSER_MAX_ROWS: usize = 10;
+/// Show the remaining character count once the message gets this long.
+const COUNTER_THRESHOLD: usize = 1800;
+
+/// Enter sends and Shift+Enter adds a line, instead of the multi-line input's defaults.
+/// While the autocomplete popup is open the arrows, Tab and Enter drive it instead.
+pub fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("enter", SendMessage, Some("Composer > Input")),
+        KeyBinding::new("shift-enter", InsertNewline, Some("Composer > Input")),
+        KeyBinding::new("down", SelectNextSuggestion, Some("Autocomplete > Input")),
+        KeyBinding::new("up", SelectPrevSuggestion, Some("Autocomplete > Input")),
+        KeyBinding::new("tab", AcceptSuggestion, Some("Autocomplete > Input")),
+        KeyBinding::new("enter", AcceptSuggestion, Some("Autocomplete > Input")),
+        KeyBinding::new("escape", DismissAutocomplete, Some("Autocomplete > Input")),
+    ]);
+}
+
+/// Discord keeps a typing indicator up for about 10 seconds, so re-send a bit before that.
+const TYPING_TRIGGER_INTERVAL: Duration = Duration::from_secs(8);
+
+fn render_typing_indicator(names: &[String]) -> impl IntoElement {
+    let text = match names {
+        [] => String::new(),
+        [one] => format!("{} is typing…", one),
+        [first, second] => format!("{} and {} are typing…", first, second),
+        [first, second, third] => format!("{}, {} and {} are typing…", first, second, third),
+        _ => "Several people are typing…".to_string(),
+    };
+
+    div()
+        .h(px(20.))
+        .px_1()
+        .text_xs()
+        .text_color(gpui::rgb(0xb5bac1))
+        .when(!names.is_empty(), |this| {
+            this.child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(div().font_weight(gpui::FontWeight::BOLD).child("•••"))
+                    .child(text)
+            )
+        })
+}
+
+/// Consecutive messages from the same author within this window share one header.
+const GROUP_WINDOW_SECS: i64 = 7 * 60;
+
+/// Whether `msg` continues the group started by `previous`.
+fn is_grouped(previous: &MessageInfo, msg: &MessageInfo) -> bool {
+    previous.author_id == msg.author_id
+        && local_date(previous.timestamp) == local_date(msg.timestamp)
+        && (msg.timestamp - previous.timestamp).num_seconds() < GROUP_WINDOW_SECS
+}
+
+fn local_date(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+/// Timestamp text that shows the full date and time on hover.
+fn render_time(id: (&'static str, u64), text: String, time: DateTime<Utc>, settings: &Settings) -> gpui::Stateful<gpui::Div> {
+    let full = settings.format_full(time);
+
+    div()
+        .id(id)
+        .text_xs()
+        .text_color(gpui::rgb(0x949ba4))
+        .tooltip(move |window, cx| Tooltip::new(full.clone()).build(window, cx))
+        .child(text)
+}
+
+fn render_day_divider(time: DateTime<Utc>, settings: &Settings) -> impl IntoElement {
+    div()
+        .flex()
+        .items_center()
+        .gap_2()
+        .mx_4()
+        .my_2()
+        .child(div().flex_1().h(px(1.)).bg(gpui::rgb(0x3f4147)))
+        .child(
+            div()
+                .text_xs()
+                .font_weight(gpui::FontWeight::SEMIBOLD)
+                .text_color(gpui::rgb(0x949ba4))
+                .child(settings.format_long_date(time))
+        )
+        .child(div().flex_1().h(px(1.)).bg(gpui::rgb(0x3f4147)))
+}
+
+/// Tints messages that ping us.
+fn highlight_mention<E: Styled>(this: E) -> E {
+    this.bg(gpui::rgba(0xf0b2321a))
+        .border_l_2()
+        .border_color(gpui::rgb(0xf0b232))
+}
+
+fn render_new_messages_divider() -> impl IntoElement {
+    div()
+        .flex()
+        .items_center()
+        .gap_2()
+        .mx_4()
+        .my_1()
+        .child(div().flex_1().h(px(1.)).bg(gpui::rgb(0xf23f43)))
+        .child(
+            div()
+                .px_1()
+                .rounded_sm()
+                .bg(gpui::rgb(0xf23f43))
+                .text_xs()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(gpui::rgb(0xffffff))
+                .child("NEW")
+        )
+}
+
+fn render_author_name(msg: &MessageInfo, cx: &mut Context<ChannelView>) -> impl IntoElement {
+    div()
+        .id(("message-author", msg.id.get()))
+        .flex_shrink_0()
+        .cursor_pointer()
+        .hover(|s| s.underline())
+        .on_click({
+            let msg = msg.clone();
+            cx.listener(move |view, event: &ClickEvent, _, cx| {
+                view.open_profile(&msg, event.position(), cx);
+            })
+        })
+        .font_weight(gpui::FontWeight::MEDIUM)
+        .text_color(gpui::rgb(msg.author_color.unwrap_or(0xf2f3f5)))
+        .child(msg.author_name.clone())
+}
+
+fn render_message_body(msg: &MessageInfo) -> impl IntoElement {
+    let image_attachments: Vec<AttachmentInfo> = msg.attachments
+        .iter()
+        .filter(|att| att.is_image())
+        .cloned()
+        .collect();
+
+    div()
+        .flex()
+        .flex_col()
+        .when_some(msg.interaction.clone(), |this, interaction| {
+            this.child(
+                div()
+                    .text_xs()
+                    .text_color(gpui::rgb(0x949ba4))
+                    .child(interaction)
+            )
+        })
+        .when(!msg.content.is_empty(), |this| {
+            this.child(
+                // Message content
+                div()
+                    .text_color(gpui::rgb(0xdbdee1))
+                    .line_height(px(22.))
+                    .child(msg.content.clone())
+            )
+        })
+        .children(
+            image_attachments.into_iter().map(|attachment| {
+                render_image_attachment(attachment)
+            })
+        )
+        .child(render_components(msg))
+        .when(msg.ephemeral, |this| {
+            this.child(
+                div()
+                    .text_xs()
+                    .text_color(gpui::rgb(0x949ba4))
+                    .child("Only you can see this")
+            )
+        })
+}
+
+/// Actions floating over the top right corner of a hovered message.
+fn render_message_toolbar(msg: &MessageInfo, group: &str, cx: &mut Context<ChannelView>) -> impl IntoElement {
+    let message_id = msg.id;
+
+    div()
+        .absolute()
+        .top(px(-12.))
+        .right(px(16.))
+        .invisible()
+        .group_hover(group.to_string(), |s| s.visible())
+        .flex()
+        .rounded_md()
+        .border_1()
+        .border_color(gpui::rgb(0x1e1f22))
+        .bg(gpui::rgb(0x313338))
+        .child(
+            Button::new(("message-react", message_id.get()))
+                .ghost()
+                .xsmall()
+                .tooltip("Add Reaction")
+                .child("😀")
+                .on_click(cx.listener(move |view, event: &ClickEvent, window, cx| {
+                    view.open_emoji_picker(EmojiTarget::Reaction(message_id), event.position(), window, cx);
+                }))
+        )
+}
+
+fn render_message(msg: MessageInfo, settings: &Settings, cx: &mut Context<ChannelView>) -> impl IntoElement {
+    let group = format!("message-{}", msg.id);
     let avatar = if let Some(url) = &msg.author_avatar_url {
         Avatar::new()
             .src(url.as_str())
@@ -163,23 +1182,30 @@ fn render_message(msg: MessageInfo) -> impl IntoElement {
             .with_size(px(40.))
     };
 
-    let image_attachments: Vec<AttachmentInfo> = msg.attachments
-        .iter()
-        .filter(|att| att.is_image())
-        .cloned()
-        .collect();
-
     div()
+        .group(group.clone())
+        .relative()
         .w_full()
         .flex()
         .gap_4()
         .px_4()
-        .py_1()
+        .pt_2()
+        .pb_0p5()
         .hover(|s| s.bg(gpui::rgb(0x2e3035)))
+        .when(msg.mentions_me, highlight_mention)
+        .child(render_message_toolbar(&msg, &group, cx))
         .child(
             div()
+                .id(("message-avatar", msg.id.get()))
                 .flex_shrink_0()
                 .pt_1()
+                .cursor_pointer()
+                .on_click({
+                    let msg = msg.clone();
+                    cx.listener(move |view, event: &ClickEvent, _, cx| {
+                        view.open_profile(&msg, event.position(), cx);
+                    })
+                })
                 .child(avatar)
         )
         .child(
@@ -194,36 +1220,134 @@ fn render_message(msg: MessageInfo) -> impl IntoElement {
                         .flex()
                         .items_baseline()
                         .gap_2()
-                        .child(
-                            div()
-                                .font_weight(gpui::FontWeight::MEDIUM)
-                                .text_color(gpui::rgb(0xf2f3f5))
-                                .child(msg.author_name.clone())
-                        )
-                        .child(
-                            div()
-                                .text_xs()
-                                .text_color(gpui::rgb(0x949ba4))
-                                .child(format_timestamp(&msg.timestamp))
-                        )
-                )
-                .when(!msg.content.is_empty(), |this| {
-                    this.child(
-                        // Message content
-                        div()
-                            .text_color(gpui::rgb(0xdbdee1))
-                            .line_height(px(22.))
-                            .child(msg.content.clone())
-                    )
-                })
-                .children(
-                    image_attachments.into_iter().map(|attachment| {
-                        render_image_attachment(attachment)
-                    })
+                        .child(render_author_name(&msg, cx))
+                        .child(render_time(
+                            ("message-time", msg.id.get()),
+                            settings.format_timestamp(msg.timestamp),
+                            msg.timestamp,
+                            settings,
+                        ))
                 )
+                .child(render_message_body(&msg))
+        )
+}
+
+/// Follow-up message in a cozy group: no avatar or name, time shown on hover.
+fn render_message_continuation(msg: MessageInfo, settings: &Settings, cx: &mut Context<ChannelView>) -> impl IntoElement {
+    let group = format!("message-{}", msg.id);
+
+    div()
+        .group(group.clone())
+        .relative()
+        .w_full()
+        .flex()
+        .gap_4()
+        .px_4()
+        .py_0p5()
+        .hover(|s| s.bg(gpui::rgb(0x2e3035)))
+        .when(msg.mentions_me, highlight_mention)
+        .child(render_message_toolbar(&msg, &group, cx))
+        .child(
+            render_time(
+                ("message-time", msg.id.get()),
+                settings.format_time(msg.timestamp),
+                msg.timestamp,
+                settings,
+            )
+            .w(px(40.))
+            .flex_shrink_0()
+            .pt_1()
+            .invisible()
+            .group_hover(group, |s| s.visible())
+        )
+        .child(
+            div()
+                .flex_1()
+                .min_w(px(0.))
+                .child(render_message_body(&msg))
         )
 }
 
+fn render_message_compact(msg: MessageInfo, settings: &Settings, cx: &mut Context<ChannelView>) -> impl IntoElement {
+    let group = format!("message-{}", msg.id);
+
+    div()
+        .group(group.clone())
+        .relative()
+        .w_full()
+        .flex()
+        .items_start()
+        .gap_2()
+        .px_4()
+        .py_0p5()
+        .hover(|s| s.bg(gpui::rgb(0x2e3035)))
+        .when(msg.mentions_me, highlight_mention)
+        .child(render_message_toolbar(&msg, &group, cx))
+        .child(
+            render_time(
+                ("message-time", msg.id.get()),
+                settings.format_time(msg.timestamp),
+                msg.timestamp,
+                settings,
+            )
+            .w(px(64.))
+            .flex_shrink_0()
+            .pt_0p5()
+        )
+        .child(render_author_name(&msg, cx))
+        .child(
+            div()
+                .flex_1()
+                .min_w(px(0.))
+                .child(render_message_body(&msg))
+        )
+}
+
+/// Lays out the message list, inserting day dividers and collapsing groups
+/// according to the display setting.
+fn render_messages(
+    messages: Vec<MessageInfo>,
+    new_messages_since: Option<Id<MessageMarker>>,
+    jump_to: Option<Id<MessageMarker>>,
+    settings: &Settings,
+    cx: &mut Context<ChannelView>,
+) -> (Vec<gpui::AnyElement>, Option<usize>) {
+    let mut elements = Vec::with_capacity(messages.len());
+    let mut jump_index = None;
+    let mut previous: Option<MessageInfo> = None;
+    let mut unread_divider_shown = false;
+
+    for msg in messages {
+        let first_unread = !unread_divider_shown
+            && new_messages_since.map(|since| msg.id > since).unwrap_or(false);
+        if first_unread {
+            unread_divider_shown = true;
+            elements.push(render_new_messages_divider().into_any_element());
+        }
+
+        let new_day = previous.as_ref()
+            .map(|prev| local_date(prev.timestamp) != local_date(msg.timestamp))
+            .unwrap_or(true);
+        if new_day {
+            elements.push(render_day_divider(msg.timestamp, settings).into_any_element());
+        }
+
+        let grouped = !first_unread && previous.as_ref().map(|prev| is_grouped(prev, &msg)).unwrap_or(false);
+        if jump_to == Some(msg.id) {
+            jump_index = Some(elements.len());
+        }
+        previous = Some(msg.clone());
+
+        elements.push(match settings.message_display {
+            MessageDisplay::Cozy if grouped => render_message_continuation(msg, settings, cx).into_any_element(),
+            MessageDisplay::Cozy => render_message(msg, settings, cx).into_any_element(),
+            MessageDisplay::Compact => render_message_compact(msg, settings, cx).into_any_element(),
+        });
+    }
+
+    (elements, jump_index)
+}
+
 fn render_image_attachment(attachment: AttachmentInfo) -> impl IntoElement {
     // Calculate constrained dimensions (max 400x300)
     let max_width = 400.0_f32;
@@ -285,34 +1409,6 @@ fn render_image_attachment(attachment: AttachmentInfo) -> impl IntoElement {
         )
 }
 
-fn format_timestamp(timestamp: &str) -> String {
-    
-    if let Ok(secs) = timestamp.parse::<i64>() {
-        let msg_time = Local.timestamp_opt(secs, 0).single();
-        let now = Local::now();
-        
-        if let Some(msg_time) = msg_time {
-            let msg_date = msg_time.date_naive();
-            let today = now.date_naive();
-            let yesterday = today.pred_opt().unwrap_or(today);
-            
-            let time_str = msg_time.format("%-I:%M %p").to_string();
-            
-            if msg_date == today {
-                format!("Today at {}", time_str)
-            } else if msg_date == yesterday {
-                format!("Yesterday at {}", time_str)
-            } else {
-                msg_time.format("%m/%d/%Y").to_string()
-            }
-        } else {
-            timestamp.to_string()
-        }
-    } else {
-        timestamp.to_string()
-    }
-}
-
 impl Render for ChannelView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let app = self.app.clone();
@@ -332,6 +1428,130 @@ impl Render for ChannelView {
         let server_list_el = server_list_view_entity.update(cx, |view, cx| {
             div().child(view.render(window, cx))
         });
+
+        let profile_el = self.profile.as_ref().map(|profile| {
+            let position = profile.position;
+            let profile_view_el = profile.view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                anchored()
+                    .position(position)
+                    .snap_to_window_with_margin(px(8.))
+                    .child(profile_view_el)
+            )
+            .with_priority(1)
+        });
+
+        let emoji_picker_el = self.emoji_picker.as_ref().map(|picker| {
+            let position = picker.position;
+            let picker_view_el = picker.view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                anchored()
+                    .position(position)
+                    .snap_to_window_with_margin(px(8.))
+                    .child(picker_view_el)
+            )
+            .with_priority(1)
+        });
+
+        let status_menu_el = self.status_menu.as_ref().map(|menu| {
+            let position = menu.position;
+            let menu_view_el = menu.view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                anchored()
+                    .position(position)
+                    .snap_to_window_with_margin(px(8.))
+                    .child(menu_view_el)
+            )
+            .with_priority(1)
+        });
+
+        let console_el = self.console.as_ref().map(|(view, _)| {
+            let console_view_el = view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                div()
+                    .absolute()
+                    .inset_0()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .bg(gpui::hsla(0., 0., 0., 0.6))
+                    .child(console_view_el)
+            )
+            .with_priority(2)
+        });
+
+        let interaction_inbox_el = self.interaction_inbox.as_ref().map(|(view, _)| {
+            let inbox_view_el = view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                div()
+                    .absolute()
+                    .inset_0()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .bg(gpui::hsla(0., 0., 0., 0.6))
+                    .child(inbox_view_el)
+            )
+            .with_priority(2)
+        });
+
+        let rate_limits_el = self.rate_limits.as_ref().map(|(view, _)| {
+            let rate_limits_view_el = view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                div()
+                    .absolute()
+                    .inset_0()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .bg(gpui::hsla(0., 0., 0., 0.6))
+                    .child(rate_limits_view_el)
+            )
+            .with_priority(2)
+        });
+
+        let mentions_el = self.mentions.as_ref().map(|(view, _)| {
+            let mentions_view_el = view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                div()
+                    .absolute()
+                    .top(px(48.))
+                    .right(px(16.))
+                    .child(mentions_view_el)
+            )
+            .with_priority(1)
+        });
+
+        let settings_el = self.settings.as_ref().map(|(view, _)| {
+            let settings_view_el = view.clone().update(cx, |view, cx| {
+                div().child(view.render(window, cx))
+            });
+            deferred(
+                div()
+                    .absolute()
+                    .inset_0()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .bg(gpui::hsla(0., 0., 0., 0.6))
+                    .child(settings_view_el)
+            )
+            .with_priority(2)
+        });
         
         if has_guild {
             let channels_el = channels_view_entity.update(cx, |view, cx| {
@@ -340,16 +1560,60 @@ impl Render for ChannelView {
                     .child(view.render(window, cx))
             });
             
+            let member_list_el = if self.is_member_list_shown() {
+                let member_list_view_entity = self.member_list_view
+                    .get_or_insert_with(|| cx.new(|cx| MemberListView::new(window, app.clone(), cx)))
+                    .clone();
+                Some(member_list_view_entity.update(cx, |view, cx| {
+                    div()
+                        .h_full()
+                        .child(view.render(window, cx))
+                }))
+            } else {
+                None
+            };
+
             let message_view_el = self.render_message_view(window, cx);
             
             div()
+                .relative()
                 .flex()
                 .size_full()
                 .child(server_list_el)
                 .child(channels_el)
                 .child(message_view_el)
+                .children(member_list_el)
+                .children(profile_el)
+                .children(emoji_picker_el)
+                .children(status_menu_el)
+                .children(mentions_el)
+                .children(settings_el)
+                .children(console_el)
+                .children(interaction_inbox_el)
+                .children(rate_limits_el)
+                .children(Root::render_notification_layer(window, cx))
+        } else if self.has_selected_channel() {
+            // Direct message opened from a profile
+            let message_view_el = self.render_message_view(window, cx);
+
+            div()
+                .relative()
+                .flex()
+                .size_full()
+                .child(server_list_el)
+                .child(message_view_el)
+                .children(profile_el)
+                .children(emoji_picker_el)
+                .children(status_menu_el)
+                .children(mentions_el)
+                .children(settings_el)
+                .children(console_el)
+                .children(interaction_inbox_el)
+                .children(rate_limits_el)
+                .children(Root::render_notification_layer(window, cx))
         } else {
             div()
+                .relative()
                 .flex()
                 .size_full()
                 .child(server_list_el)
@@ -374,6 +1638,15 @@ impl Render for ChannelView {
                                 )
                         )
                 )
+                .children(profile_el)
+                .children(emoji_picker_el)
+                .children(status_menu_el)
+                .children(mentions_el)
+                .children(settings_el)
+                .children(console_el)
+                .children(interaction_inbox_el)
+                .children(rate_limits_el)
+                .children(Root::render_notification_layer(window, cx))
         }
     }
 }
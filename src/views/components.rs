@@ -0,0 +1,193 @@
+use gpui::{IntoElement, ParentElement, SharedString, Styled, div, px, prelude::*, img, InteractiveElement, SharedUri};
+use gpui_component::tooltip::Tooltip;
+use twilight_model::channel::message::component::{Button as ButtonComponent, ButtonStyle, SelectMenu};
+use twilight_model::channel::message::{Component, EmojiReactionType};
+use crate::app::{EmojiInfo, MessageInfo};
+
+/// Shown on controls that only a user account could use. Discord doesn't accept
+/// component interactions from bots.
+const BOT_CANT_INTERACT: &str = "Bot accounts can't use message components";
+
+fn emoji_element(emoji: &EmojiReactionType) -> gpui::AnyElement {
+    match emoji {
+        EmojiReactionType::Custom { animated, id, name } => {
+            let info = EmojiInfo { id: *id, name: name.clone().unwrap_or_default(), animated: *animated };
+            div()
+                .size(px(18.))
+                .child(img(SharedUri::from(info.url())).size_full())
+                .into_any_element()
+        }
+        EmojiReactionType::Unicode { name } => div().child(name.clone()).into_any_element(),
+    }
+}
+
+/// Everything under the message text: action rows and the layout components.
+/// Link buttons and files open in the browser; everything else is display only.
+pub fn render_components(msg: &MessageInfo) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .when(!msg.components.is_empty(), |this| this.mt_1())
+        .children(msg.components.iter().enumerate().map(|(index, component)| {
+            render_component(component, format!("component-{}-{}", msg.id, index))
+        }))
+}
+
+fn render_component(component: &Component, key: String) -> gpui::AnyElement {
+    match component {
+        Component::ActionRow(row) => div()
+            .flex()
+            .flex_wrap()
+            .gap_2()
+            .children(row.components.iter().enumerate().map(|(index, component)| {
+                render_component(component, format!("{}-{}", key, index))
+            }))
+            .into_any_element(),
+        Component::Button(button) => render_button(button, key).into_any_element(),
+        Component::SelectMenu(menu) => render_select_menu(menu, key).into_any_element(),
+        Component::TextDisplay(text) => div()
+            .text_color(gpui::rgb(0xdbdee1))
+            .line_height(px(22.))
+            .child(text.content.clone())
+            .into_any_element(),
+        Component::Section(section) => div()
+            .flex()
+            .items_start()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .min_w(px(0.))
+                    .children(section.components.iter().enumerate().map(|(index, component)| {
+                        render_component(component, format!("{}-{}", key, index))
+                    }))
+            )
+            .child(render_component(&section.accessory, format!("{}-accessory", key)))
+            .into_any_element(),
+        Component::Thumbnail(thumbnail) => div()
+            .flex_shrink_0()
+            .size(px(85.))
+            .rounded(px(8.))
+            .overflow_hidden()
+            .child(img(SharedUri::from(thumbnail.media.url.clone())).size_full())
+            .into_any_element(),
+        Component::MediaGallery(gallery) => div()
+            .flex()
+            .flex_wrap()
+            .gap_1()
+            .max_w(px(520.))
+            .children(gallery.items.iter().map(|item| {
+                div()
+                    .w(px(172.))
+                    .h(px(172.))
+                    .rounded(px(8.))
+                    .overflow_hidden()
+                    .child(img(SharedUri::from(item.media.url.clone())).size_full())
+            }))
+            .into_any_element(),
+        Component::File(file) => {
+            let url = file.file.url.clone();
+            let name = url.rsplit('/').next().unwrap_or_default().to_string();
+            div()
+                .id(SharedString::from(key))
+                .flex()
+                .items_center()
+                .gap_2()
+                .max_w(px(432.))
+                .p_3()
+                .rounded(px(8.))
+                .border_1()
+                .border_color(gpui::rgb(0x1e1f22))
+                .bg(gpui::rgb(0x2b2d31))
+                .cursor_pointer()
+                .text_color(gpui::rgb(0x00a8fc))
+                .hover(|s| s.underline())
+                .on_click(move |_, _, cx| cx.open_url(&url))
+                .child("📄")
+                .child(name)
+                .into_any_element()
+        }
+        Component::Separator(separator) => div()
+            .my_1()
+            .when(separator.divider.unwrap_or(true), |this| this.h(px(1.)).bg(gpui::rgb(0x3f4147)))
+            .into_any_element(),
+        Component::Container(container) => div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .max_w(px(520.))
+            .p_4()
+            .rounded(px(8.))
+            .border_l_4()
+            .border_color(gpui::rgb(0x4e5058))
+            .bg(gpui::rgb(0x2b2d31))
+            .children(container.components.iter().enumerate().map(|(index, component)| {
+                render_component(component, format!("{}-{}", key, index))
+            }))
+            .into_any_element(),
+        // Text inputs only appear in modals
+        _ => div().into_any_element(),
+    }
+}
+
+fn render_button(button: &ButtonComponent, key: String) -> impl IntoElement {
+    let (background, hover) = match button.style {
+        ButtonStyle::Primary => (0x5865f2, 0x4752c4),
+        ButtonStyle::Success => (0x248046, 0x1a6334),
+        ButtonStyle::Danger => (0xda373c, 0xa12828),
+        _ => (0x4e5058, 0x6d6f78),
+    };
+    let is_link = button.style == ButtonStyle::Link;
+    let disabled = button.disabled;
+
+    div()
+        .id(SharedString::from(key))
+        .flex()
+        .items_center()
+        .gap_1()
+        .h(px(32.))
+        .px_4()
+        .rounded_sm()
+        .text_sm()
+        .font_weight(gpui::FontWeight::MEDIUM)
+        .text_color(gpui::rgb(0xffffff))
+        .bg(gpui::rgb(background))
+        .when(disabled, |this| this.opacity(0.5).cursor_not_allowed())
+        .when(!disabled, |this| match &button.url {
+            Some(url) => {
+                let url = url.clone();
+                this.cursor_pointer()
+                    .hover(|s| s.bg(gpui::rgb(hover)))
+                    .on_click(move |_, _, cx| cx.open_url(&url))
+            }
+            None => this.tooltip(|window, cx| Tooltip::new(BOT_CANT_INTERACT).build(window, cx)),
+        })
+        .children(button.emoji.as_ref().map(emoji_element))
+        .children(button.label.clone())
+        .when(is_link, |this| this.child("↗"))
+}
+
+fn render_select_menu(menu: &SelectMenu, key: String) -> impl IntoElement {
+    let placeholder = menu.placeholder.clone().unwrap_or_else(|| "Make a selection".to_string());
+
+    div()
+        .id(SharedString::from(key))
+        .flex()
+        .items_center()
+        .w_full()
+        .max_w(px(400.))
+        .h(px(40.))
+        .px_3()
+        .rounded_sm()
+        .border_1()
+        .border_color(gpui::rgb(0x1e1f22))
+        .bg(gpui::rgb(0x1e1f22))
+        .text_color(gpui::rgb(0x949ba4))
+        .when(menu.disabled, |this| this.opacity(0.5))
+        .when(!menu.disabled, |this| this.tooltip(|window, cx| Tooltip::new(BOT_CANT_INTERACT).build(window, cx)))
+        .child(div().flex_1().child(placeholder))
+        .child("▾")
+}
@@ -0,0 +1,486 @@
+use gpui::{
+    Context, DismissEvent, Entity, EventEmitter, IntoElement, ParentElement, PathPromptOptions, Render, Styled, Window,
+    div, px, prelude::*, InteractiveElement,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::label::Label;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::Sizable;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+use crate::app::{AppState, CommandInfo};
+use crate::services::command_validation::{command_kind, validate_command, validate_commands};
+use crate::services::discord::DiscordService;
+
+/// Fields Discord fills in, left out of exports so they can be imported into another application.
+const READ_ONLY_FIELDS: [&str; 4] = ["id", "application_id", "guild_id", "version"];
+
+fn new_command_template() -> Value {
+    json!({
+        "type": 1,
+        "name": "new-command",
+        "description": "Describe what it does",
+        "options": [],
+        "default_member_permissions": null,
+        "name_localizations": {},
+        "description_localizations": {}
+    })
+}
+
+fn portable(command: &Value) -> Value {
+    let mut command = command.clone();
+    if let Some(fields) = command.as_object_mut() {
+        for field in READ_ONLY_FIELDS {
+            fields.remove(field);
+        }
+    }
+    command
+}
+
+/// Lets a bot developer manage the application's global and guild commands
+/// as JSON, checked against Discord's limits before anything is sent.
+pub struct DevConsoleView {
+    app: Arc<Mutex<AppState>>,
+    editor: Entity<InputState>,
+    /// Id of the command loaded into the editor.
+    selected: Option<String>,
+    problems: Vec<String>,
+    notice: Option<String>,
+    /// Imported commands waiting for confirmation, since they replace the whole scope.
+    pending_import: Option<Vec<Value>>,
+}
+
+impl EventEmitter<DismissEvent> for DevConsoleView {}
+
+impl DevConsoleView {
+    /// Opens on `command` and its scope when given, otherwise on a new command.
+    pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, command: Option<CommandInfo>, cx: &mut Context<Self>) -> Self {
+        let loaded = command.as_ref().map(|command| command.definition.clone()).unwrap_or_else(new_command_template);
+        let editor = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).auto_grow(16, 24);
+            state.set_value(pretty(&loaded), window, cx);
+            state
+        });
+        let scope = match &command {
+            Some(command) => command.guild_id,
+            None => app.lock().ok().and_then(|app| app.console_scope),
+        };
+        DiscordService::fetch_console_commands(app.clone(), scope);
+
+        Self {
+            app,
+            editor,
+            selected: command.map(|command| command.id.to_string()),
+            problems: Vec::new(),
+            notice: None,
+            pending_import: None,
+        }
+    }
+
+    fn scope(&self) -> Option<Id<GuildMarker>> {
+        self.app.lock().ok().and_then(|app| app.console_scope)
+    }
+
+    fn set_scope(&mut self, scope: Option<Id<GuildMarker>>, window: &mut Window, cx: &mut Context<Self>) {
+        if let Ok(mut app) = self.app.lock() {
+            app.console_commands.clear();
+        }
+        DiscordService::fetch_console_commands(self.app.clone(), scope);
+        self.new_command(window, cx);
+    }
+
+    fn load(&mut self, command: &Value, window: &mut Window, cx: &mut Context<Self>) {
+        self.selected = command["id"].as_str().map(str::to_string);
+        self.problems.clear();
+        self.notice = None;
+        self.editor.update(cx, |state, cx| state.set_value(pretty(command), window, cx));
+        cx.notify();
+    }
+
+    fn new_command(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.load(&new_command_template(), window, cx);
+    }
+
+    /// The editor contents, if they parse and pass validation.
+    fn checked_command(&mut self, cx: &mut Context<Self>) -> Option<Value> {
+        self.notice = None;
+        let text = self.editor.read(cx).value().to_string();
+        let command = match serde_json::from_str::<Value>(&text) {
+            Ok(command) => command,
+            Err(e) => {
+                self.problems = vec![format!("Invalid JSON: {}", e)];
+                cx.notify();
+                return None;
+            }
+        };
+        self.problems = validate_command(&command);
+        cx.notify();
+        self.problems.is_empty().then_some(command)
+    }
+
+    fn validate(&mut self, cx: &mut Context<Self>) {
+        if self.checked_command(cx).is_some() {
+            self.notice = Some("No problems found.".to_string());
+        }
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let Some(command) = self.checked_command(cx) else {
+            return;
+        };
+        let notice = if command["id"].is_string() { "Saving changes…" } else { "Creating command…" };
+        self.notice = Some(notice.to_string());
+        DiscordService::save_console_command(self.app.clone(), self.scope(), command);
+    }
+
+    fn delete(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(command_id) = self.selected.clone() else {
+            return;
+        };
+        DiscordService::delete_console_command(self.app.clone(), self.scope(), command_id);
+        self.new_command(window, cx);
+    }
+
+    fn export(&mut self, cx: &mut Context<Self>) {
+        let (commands, scope) = self.app.lock()
+            .map(|app| (app.console_commands.iter().map(portable).collect::<Vec<_>>(), app.console_scope))
+            .unwrap_or_default();
+        let file_name = match scope {
+            Some(guild_id) => format!("commands-{}.json", guild_id),
+            None => "commands-global.json".to_string(),
+        };
+        let directory = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Export here".into()),
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = directory.await else {
+                return;
+            };
+            let Some(directory) = paths.into_iter().next() else {
+                return;
+            };
+            let path = directory.join(file_name);
+            let notice = match std::fs::write(&path, pretty(&Value::Array(commands))) {
+                Ok(()) => format!("Exported to {}", path.display()),
+                Err(e) => {
+                    eprintln!("Failed to export commands to {}: {}", path.display(), e);
+                    format!("Couldn't export: {}", e)
+                }
+            };
+            let _ = this.update(cx, |view, cx| {
+                view.notice = Some(notice);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn import(&mut self, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let result = read_commands(&path);
+            let _ = this.update(cx, |view, cx| {
+                match result {
+                    Ok(commands) => {
+                        view.problems = validate_commands(&commands);
+                        view.notice = None;
+                        if view.problems.is_empty() {
+                            view.pending_import = Some(commands);
+                        }
+                    }
+                    Err(e) => view.problems = vec![e],
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn confirm_import(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commands) = self.pending_import.take() else {
+            return;
+        };
+        DiscordService::overwrite_console_commands(self.app.clone(), self.scope(), commands);
+        self.new_command(window, cx);
+        self.notice = Some("Importing…".to_string());
+    }
+
+    fn render_scope_tab(&self, id: impl Into<gpui::ElementId>, label: String, scope: Option<Id<GuildMarker>>, current: Option<Id<GuildMarker>>, cx: &mut Context<Self>) -> impl IntoElement {
+        let button = Button::new(id)
+            .small()
+            .child(label)
+            .on_click(cx.listener(move |view, _, window, cx| view.set_scope(scope, window, cx)));
+        if scope == current {
+            button.primary()
+        } else {
+            button.ghost()
+        }
+    }
+
+    fn render_import_prompt(&self, count: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let existing = self.app.lock().map(|app| app.console_commands.len()).unwrap_or(0);
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .p_2()
+            .rounded_md()
+            .bg(gpui::rgb(0x2b2d31))
+            .child(
+                div()
+                    .flex_1()
+                    .text_sm()
+                    .text_color(gpui::rgb(0xdbdee1))
+                    .child(format!("Replace the {} existing commands with {} imported ones?", existing, count))
+            )
+            .child(
+                Button::new("console-import-cancel")
+                    .ghost()
+                    .small()
+                    .child("Cancel")
+                    .on_click(cx.listener(|view, _, _, cx| {
+                        view.pending_import = None;
+                        cx.notify();
+                    }))
+            )
+            .child(
+                Button::new("console-import-confirm")
+                    .danger()
+                    .small()
+                    .child("Replace")
+                    .on_click(cx.listener(|view, _, window, cx| view.confirm_import(window, cx)))
+            )
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Reads an exported file: a list of commands, or a single one.
+fn read_commands(path: &Path) -> Result<Vec<Value>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    match serde_json::from_str::<Value>(&text) {
+        Ok(Value::Array(commands)) => Ok(commands),
+        Ok(command @ Value::Object(_)) => Ok(vec![command]),
+        Ok(_) => Err("Expected a command or a list of commands.".to_string()),
+        Err(e) => Err(format!("Invalid JSON: {}", e)),
+    }
+}
+
+impl Render for DevConsoleView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (commands, scope, guilds) = self.app.lock()
+            .map(|app| {
+                let guilds: Vec<_> = app.guilds.iter().map(|guild| (guild.id, guild.name.clone())).collect();
+                (app.console_commands.clone(), app.console_scope, guilds)
+            })
+            .unwrap_or_default();
+        let pending_import = self.pending_import.as_ref().map(Vec::len);
+
+        let mut tabs = vec![
+            self.render_scope_tab("console-scope-global", "Global".to_string(), None, scope, cx).into_any_element(),
+        ];
+        for (guild_id, name) in guilds {
+            tabs.push(
+                self.render_scope_tab(("console-scope", guild_id.get()), name, Some(guild_id), scope, cx)
+                    .into_any_element(),
+            );
+        }
+
+        div()
+            .id("dev-console")
+            .occlude()
+            .flex()
+            .flex_col()
+            .w(px(860.))
+            .h(px(620.))
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .p_3()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new("Developer Console").text_color(gpui::rgb(0xf2f3f5)))
+                    .child(
+                        div()
+                            .flex()
+                            .gap_1()
+                            .child(
+                                Button::new("console-import")
+                                    .ghost()
+                                    .small()
+                                    .child("Import")
+                                    .on_click(cx.listener(|view, _, _, cx| view.import(cx)))
+                            )
+                            .child(
+                                Button::new("console-export")
+                                    .ghost()
+                                    .small()
+                                    .child("Export")
+                                    .on_click(cx.listener(|view, _, _, cx| view.export(cx)))
+                            )
+                            .child(
+                                Button::new("console-close")
+                                    .ghost()
+                                    .small()
+                                    .child("Close")
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                            )
+                    )
+            )
+            .child(
+                div()
+                    .id("console-scopes")
+                    .flex()
+                    .gap_1()
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .overflow_x_scrollbar()
+                    .children(tabs)
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .child(
+                        div()
+                            .id("console-commands")
+                            .flex()
+                            .flex_col()
+                            .w(px(240.))
+                            .p_2()
+                            .gap_1()
+                            .bg(gpui::rgb(0x2b2d31))
+                            .overflow_y_scrollbar()
+                            .child(
+                                Button::new("console-new")
+                                    .primary()
+                                    .small()
+                                    .child("New command")
+                                    .on_click(cx.listener(|view, _, window, cx| view.new_command(window, cx)))
+                            )
+                            .when(commands.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .p_2()
+                                        .text_sm()
+                                        .text_color(gpui::rgb(0x949ba4))
+                                        .child("No commands registered here.")
+                                )
+                            })
+                            .children(commands.into_iter().enumerate().map(|(index, command)| {
+                                let is_selected = self.selected.is_some()
+                                    && command["id"].as_str() == self.selected.as_deref();
+                                let name = command["name"].as_str().unwrap_or_default().to_string();
+                                let kind = command_kind(&command);
+                                div()
+                                    .id(("console-command", index))
+                                    .flex()
+                                    .flex_col()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_sm()
+                                    .cursor_pointer()
+                                    .when(is_selected, |this| this.bg(gpui::rgb(0x404249)))
+                                    .hover(|s| s.bg(gpui::rgb(0x35373c)))
+                                    .on_click(cx.listener(move |view, _, window, cx| view.load(&command, window, cx)))
+                                    .child(div().text_sm().text_color(gpui::rgb(0xf2f3f5)).child(name))
+                                    .child(div().text_xs().text_color(gpui::rgb(0x949ba4)).child(kind))
+                            }))
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .gap_2()
+                            .p_3()
+                            .child(
+                                div()
+                                    .id("console-editor")
+                                    .flex_1()
+                                    .min_h(px(0.))
+                                    .overflow_y_scrollbar()
+                                    .child(Input::new(&self.editor))
+                            )
+                            .children(pending_import.map(|count| self.render_import_prompt(count, cx)))
+                            .when(!self.problems.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .id("console-problems")
+                                        .flex()
+                                        .flex_col()
+                                        .max_h(px(120.))
+                                        .overflow_y_scrollbar()
+                                        .text_sm()
+                                        .text_color(gpui::rgb(0xf23f43))
+                                        .children(self.problems.iter().map(|problem| div().child(problem.clone())))
+                                )
+                            })
+                            .children(self.notice.clone().map(|notice| {
+                                div().text_sm().text_color(gpui::rgb(0x949ba4)).child(notice)
+                            }))
+                            .child(
+                                div()
+                                    .flex()
+                                    .justify_end()
+                                    .gap_2()
+                                    .when(self.selected.is_some(), |this| {
+                                        this.child(
+                                            Button::new("console-delete")
+                                                .danger()
+                                                .small()
+                                                .child("Delete")
+                                                .on_click(cx.listener(|view, _, window, cx| view.delete(window, cx)))
+                                        )
+                                    })
+                                    .child(
+                                        Button::new("console-validate")
+                                            .ghost()
+                                            .small()
+                                            .child("Validate")
+                                            .on_click(cx.listener(|view, _, _, cx| view.validate(cx)))
+                                    )
+                                    .child(
+                                        Button::new("console-save")
+                                            .primary()
+                                            .small()
+                                            .child("Save")
+                                            .on_click(cx.listener(|view, _, _, cx| view.save(cx)))
+                                    )
+                            )
+                    )
+            )
+    }
+}
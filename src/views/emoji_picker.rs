@@ -0,0 +1,363 @@
+use gpui::{
+    Context, DismissEvent, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled, Subscription,
+    Window, div, px, prelude::*, img, InteractiveElement, SharedUri,
+};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::tooltip::Tooltip;
+use std::sync::{Arc, Mutex};
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+use crate::app::{AppState, EmojiInfo, PickedEmoji};
+use crate::settings::SkinTone;
+
+/// Search results are capped so a one-letter query doesn't lay out every emoji.
+const MAX_SEARCH_RESULTS: usize = 200;
+
+pub enum EmojiPickerEvent {
+    Picked(PickedEmoji),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PickerTab {
+    Recent,
+    Guild(Id<GuildMarker>),
+    Group(emojis::Group),
+}
+
+/// A grid cell: what gets picked, plus what to show and the tooltip.
+struct Cell {
+    emoji: PickedEmoji,
+    shortcode: String,
+    image_url: Option<String>,
+}
+
+impl Cell {
+    fn unicode(emoji: &emojis::Emoji, tone: SkinTone) -> Self {
+        let toned = match tone_of(tone) {
+            Some(tone) if emoji.skin_tones().is_some() => emoji.with_skin_tone(tone).unwrap_or(emoji),
+            _ => emoji,
+        };
+        Cell {
+            emoji: PickedEmoji::Unicode(toned.as_str().to_string()),
+            shortcode: emoji.shortcode().unwrap_or(emoji.name()).to_string(),
+            image_url: None,
+        }
+    }
+
+    fn custom(emoji: &EmojiInfo) -> Self {
+        Cell {
+            emoji: emoji.into(),
+            shortcode: emoji.name.clone(),
+            image_url: Some(emoji.url()),
+        }
+    }
+
+    fn recent(emoji: &PickedEmoji) -> Self {
+        match emoji {
+            PickedEmoji::Unicode(glyph) => Cell {
+                emoji: emoji.clone(),
+                shortcode: emojis::get(glyph)
+                    .and_then(|e| e.shortcode())
+                    .unwrap_or_default()
+                    .to_string(),
+                image_url: None,
+            },
+            PickedEmoji::Custom { id, name, animated } => Cell {
+                emoji: emoji.clone(),
+                shortcode: name.clone(),
+                image_url: Some(
+                    EmojiInfo { id: *id, name: name.clone(), animated: *animated }.url()
+                ),
+            },
+        }
+    }
+}
+
+fn tone_of(tone: SkinTone) -> Option<emojis::SkinTone> {
+    match tone {
+        SkinTone::Default => None,
+        SkinTone::Light => Some(emojis::SkinTone::Light),
+        SkinTone::MediumLight => Some(emojis::SkinTone::MediumLight),
+        SkinTone::Medium => Some(emojis::SkinTone::Medium),
+        SkinTone::MediumDark => Some(emojis::SkinTone::MediumDark),
+        SkinTone::Dark => Some(emojis::SkinTone::Dark),
+    }
+}
+
+fn tone_preview(tone: SkinTone) -> &'static str {
+    match tone {
+        SkinTone::Default => "✋",
+        SkinTone::Light => "✋🏻",
+        SkinTone::MediumLight => "✋🏼",
+        SkinTone::Medium => "✋🏽",
+        SkinTone::MediumDark => "✋🏾",
+        SkinTone::Dark => "✋🏿",
+    }
+}
+
+/// Emoji browser used by the composer and for adding reactions.
+pub struct EmojiPickerView {
+    app: Arc<Mutex<AppState>>,
+    search: Entity<InputState>,
+    tab: PickerTab,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<EmojiPickerEvent> for EmojiPickerView {}
+impl EventEmitter<DismissEvent> for EmojiPickerView {}
+
+impl EmojiPickerView {
+    pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| InputState::new(window, cx).placeholder("Find the perfect emoji"));
+        let subscription = cx.subscribe(&search, |_, _, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        });
+        search.update(cx, |state, cx| state.focus(window, cx));
+
+        let has_recent = app.lock().map(|app| !app.recent_emoji.is_empty()).unwrap_or(false);
+        let guild_id = app.lock().ok().and_then(|app| app.selected_guild);
+        let tab = match (has_recent, guild_id) {
+            (true, _) => PickerTab::Recent,
+            (false, Some(guild_id)) => PickerTab::Guild(guild_id),
+            (false, None) => PickerTab::Group(emojis::Group::SmileysAndEmotion),
+        };
+
+        Self {
+            app,
+            search,
+            tab,
+            _subscription: subscription,
+        }
+    }
+
+    fn pick(&mut self, emoji: PickedEmoji, cx: &mut Context<Self>) {
+        if let Ok(mut app) = self.app.lock() {
+            app.record_recent_emoji(emoji.clone());
+        }
+        cx.emit(EmojiPickerEvent::Picked(emoji));
+    }
+
+    fn set_skin_tone(&mut self, tone: SkinTone, cx: &mut Context<Self>) {
+        if let Ok(mut app) = self.app.lock() {
+            app.settings.skin_tone = tone;
+            app.settings.save();
+        }
+        cx.notify();
+    }
+
+    /// Usable guilds with custom emoji, current guild first.
+    fn emoji_guilds(&self) -> Vec<(Id<GuildMarker>, String, Vec<EmojiInfo>)> {
+        self.app.lock()
+            .map(|app| {
+                let mut guilds: Vec<_> = app.usable_emoji_guilds()
+                    .into_iter()
+                    .filter_map(|guild_id| {
+                        let emojis = app.emojis.get(&guild_id).filter(|emojis| !emojis.is_empty())?;
+                        let name = app.guilds.iter()
+                            .find(|guild| guild.id == guild_id)
+                            .map(|guild| guild.name.clone())
+                            .unwrap_or_default();
+                        Some((guild_id, name, emojis.clone()))
+                    })
+                    .collect();
+                guilds.sort_by_key(|(guild_id, _, _)| Some(*guild_id) != app.selected_guild);
+                guilds
+            })
+            .unwrap_or_default()
+    }
+
+    fn cells(&self, query: &str, tone: SkinTone, guilds: &[(Id<GuildMarker>, String, Vec<EmojiInfo>)]) -> Vec<Cell> {
+        if !query.is_empty() {
+            let custom = guilds.iter()
+                .flat_map(|(_, _, emojis)| emojis)
+                .filter(|emoji| emoji.name.to_lowercase().contains(query))
+                .map(Cell::custom);
+            let unicode = emojis::iter()
+                .filter(|emoji| emoji.shortcodes().any(|code| code.contains(query)) || emoji.name().contains(query))
+                .map(|emoji| Cell::unicode(emoji, tone));
+            return custom.chain(unicode).take(MAX_SEARCH_RESULTS).collect();
+        }
+
+        match self.tab {
+            PickerTab::Recent => self.app.lock()
+                .map(|app| app.recent_emoji.iter().map(Cell::recent).collect())
+                .unwrap_or_default(),
+            PickerTab::Guild(guild_id) => guilds.iter()
+                .find(|(id, _, _)| *id == guild_id)
+                .map(|(_, _, emojis)| emojis.iter().map(Cell::custom).collect())
+                .unwrap_or_default(),
+            PickerTab::Group(group) => group.emojis().map(|emoji| Cell::unicode(emoji, tone)).collect(),
+        }
+    }
+
+    fn render_tab(&self, id: impl Into<gpui::ElementId>, tab: PickerTab, label: impl IntoElement, tooltip: String, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_selected = self.tab == tab;
+        div()
+            .id(id)
+            .flex()
+            .items_center()
+            .justify_center()
+            .size(px(28.))
+            .rounded_sm()
+            .cursor_pointer()
+            .when(is_selected, |this| this.bg(gpui::rgb(0x404249)))
+            .hover(|s| s.bg(gpui::rgb(0x35373c)))
+            .tooltip(move |window, cx| Tooltip::new(tooltip.clone()).build(window, cx))
+            .on_click(cx.listener(move |view, _, _, cx| {
+                view.tab = tab;
+                cx.notify();
+            }))
+            .child(label)
+    }
+}
+
+fn group_name(group: emojis::Group) -> &'static str {
+    match group {
+        emojis::Group::SmileysAndEmotion => "Smileys & Emotion",
+        emojis::Group::PeopleAndBody => "People & Body",
+        emojis::Group::AnimalsAndNature => "Animals & Nature",
+        emojis::Group::FoodAndDrink => "Food & Drink",
+        emojis::Group::TravelAndPlaces => "Travel & Places",
+        emojis::Group::Activities => "Activities",
+        emojis::Group::Objects => "Objects",
+        emojis::Group::Symbols => "Symbols",
+        emojis::Group::Flags => "Flags",
+    }
+}
+
+impl Render for EmojiPickerView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let query = self.search.read(cx).value().trim().to_lowercase();
+        let tone = self.app.lock()
+            .map(|app| app.settings.skin_tone)
+            .unwrap_or_default();
+        let guilds = self.emoji_guilds();
+        let cells = self.cells(&query, tone, &guilds);
+
+        let mut tabs = vec![
+            self.render_tab("emoji-tab-recent", PickerTab::Recent, "🕘", "Frequently Used".to_string(), cx)
+                .into_any_element(),
+        ];
+        for (guild_id, name, _) in &guilds {
+            let initial = name.chars().next().map(|c| c.to_string()).unwrap_or_default();
+            tabs.push(
+                self.render_tab(
+                    ("emoji-tab-guild", guild_id.get()),
+                    PickerTab::Guild(*guild_id),
+                    div().text_xs().font_weight(gpui::FontWeight::BOLD).child(initial),
+                    name.clone(),
+                    cx,
+                )
+                .into_any_element(),
+            );
+        }
+        for (index, group) in emojis::Group::iter().enumerate() {
+            let icon = group.emojis().next().map(|emoji| emoji.as_str()).unwrap_or_default();
+            tabs.push(
+                self.render_tab(
+                    ("emoji-tab-group", index),
+                    PickerTab::Group(group),
+                    icon,
+                    group_name(group).to_string(),
+                    cx,
+                )
+                .into_any_element(),
+            );
+        }
+
+        div()
+            .id("emoji-picker")
+            .occlude()
+            .flex()
+            .flex_col()
+            .w(px(380.))
+            .h(px(420.))
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x2b2d31))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(div().flex_1().child(Input::new(&self.search)))
+                    .children(SkinTone::ALL.into_iter().map(|option| {
+                        div()
+                            .id(("skin-tone", option as usize))
+                            .px_0p5()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .when(option == tone, |this| this.bg(gpui::rgb(0x404249)))
+                            .hover(|s| s.bg(gpui::rgb(0x35373c)))
+                            .on_click(cx.listener(move |view, _, _, cx| view.set_skin_tone(option, cx)))
+                            .child(tone_preview(option))
+                    }))
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .child(
+                        div()
+                            .id("emoji-tabs")
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .p_1()
+                            .bg(gpui::rgb(0x1e1f22))
+                            .overflow_y_scrollbar()
+                            .children(tabs)
+                    )
+                    .child(
+                        div()
+                            .id("emoji-grid")
+                            .flex()
+                            .flex_wrap()
+                            .content_start()
+                            .flex_1()
+                            .p_2()
+                            .overflow_y_scrollbar()
+                            .when(cells.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .p_4()
+                                        .text_sm()
+                                        .text_color(gpui::rgb(0x949ba4))
+                                        .child("No emoji found.")
+                                )
+                            })
+                            .children(cells.into_iter().enumerate().map(|(index, cell)| {
+                                let tooltip = format!(":{}:", cell.shortcode);
+                                let emoji = cell.emoji.clone();
+                                div()
+                                    .id(("emoji", index))
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .size(px(36.))
+                                    .rounded_sm()
+                                    .cursor_pointer()
+                                    .text_xl()
+                                    .hover(|s| s.bg(gpui::rgb(0x404249)))
+                                    .tooltip(move |window, cx| Tooltip::new(tooltip.clone()).build(window, cx))
+                                    .on_click(cx.listener(move |view, _, _, cx| view.pick(emoji.clone(), cx)))
+                                    .child(match (&cell.image_url, &cell.emoji) {
+                                        (Some(url), _) => div()
+                                            .size(px(28.))
+                                            .child(img(SharedUri::from(url.clone())).size_full()),
+                                        (None, PickedEmoji::Unicode(glyph)) => div().child(glyph.clone()),
+                                        (None, PickedEmoji::Custom { name, .. }) => div().child(name.clone()),
+                                    })
+                            }))
+                    )
+            )
+    }
+}
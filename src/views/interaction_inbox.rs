@@ -0,0 +1,337 @@
+use chrono::Utc;
+use gpui::{
+    Context, DismissEvent, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled, Task, Window, div, px,
+    prelude::*, InteractiveElement,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::label::Label;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{Disableable, Sizable};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::app::{
+    AppState, InteractionInfo, InteractionResponseState, AUTOCOMPLETE_INTERACTION, COMMAND_INTERACTION,
+    COMPONENT_INTERACTION, MODAL_SUBMIT_INTERACTION,
+};
+use crate::services::discord::DiscordService;
+
+/// Response bodies to start from, by button label.
+fn templates(interaction: &InteractionInfo) -> Vec<(&'static str, Value)> {
+    if interaction.state != InteractionResponseState::Pending {
+        return vec![("Edit original", json!({ "content": "Done!" }))];
+    }
+
+    let message = ("Message", json!({ "type": 4, "data": { "content": "Hello!" } }));
+    let ephemeral = ("Ephemeral", json!({ "type": 4, "data": { "content": "Only you can see this.", "flags": 64 } }));
+    let defer = ("Defer", json!({ "type": 5 }));
+    let defer_update = ("Defer update", json!({ "type": 6 }));
+    let update = ("Update message", json!({ "type": 7, "data": { "content": "Updated!" } }));
+    let modal = ("Modal", json!({
+        "type": 9,
+        "data": {
+            "custom_id": "prototype-modal",
+            "title": "Prototype",
+            "components": [{
+                "type": 1,
+                "components": [{ "type": 4, "custom_id": "answer", "label": "Answer", "style": 1 }]
+            }]
+        }
+    }));
+
+    match interaction.kind {
+        COMMAND_INTERACTION => vec![message, ephemeral, defer, modal],
+        COMPONENT_INTERACTION => vec![message, ephemeral, defer, defer_update, update, modal],
+        MODAL_SUBMIT_INTERACTION => vec![message, ephemeral, defer, defer_update, update],
+        AUTOCOMPLETE_INTERACTION => vec![(
+            "Choices",
+            json!({ "type": 8, "data": { "choices": [{ "name": "First choice", "value": "first" }] } }),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// What's left of the response window, e.g. "2s left".
+fn time_left(interaction: &InteractionInfo) -> Option<String> {
+    let left = (interaction.deadline() - Utc::now()).num_seconds();
+    if left < 0 {
+        return None;
+    }
+    Some(if left >= 60 {
+        format!("{}m left", left / 60)
+    } else {
+        format!("{}s left", left)
+    })
+}
+
+/// Lists the interactions a bot received and lets a developer answer them by hand.
+pub struct InteractionInboxView {
+    app: Arc<Mutex<AppState>>,
+    selected: Option<String>,
+    editor: Entity<InputState>,
+    error: Option<String>,
+    /// Re-renders every second so the response countdowns move.
+    _ticker: Task<()>,
+}
+
+impl EventEmitter<DismissEvent> for InteractionInboxView {}
+
+impl InteractionInboxView {
+    pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, cx: &mut Context<Self>) -> Self {
+        let editor = cx.new(|cx| InputState::new(window, cx).auto_grow(8, 14));
+        let ticker = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            app,
+            selected: None,
+            editor,
+            error: None,
+            _ticker: ticker,
+        }
+    }
+
+    fn selected_interaction(&self) -> Option<InteractionInfo> {
+        let selected = self.selected.as_ref()?;
+        self.app.lock().ok()?
+            .interactions
+            .iter()
+            .find(|interaction| interaction.id == *selected)
+            .cloned()
+    }
+
+    fn select(&mut self, interaction: &InteractionInfo, window: &mut Window, cx: &mut Context<Self>) {
+        self.selected = Some(interaction.id.clone());
+        self.error = None;
+        let template = templates(interaction).into_iter().next().map(|(_, body)| pretty(&body)).unwrap_or_default();
+        self.editor.update(cx, |state, cx| state.set_value(template, window, cx));
+        cx.notify();
+    }
+
+    fn use_template(&mut self, body: &Value, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor.update(cx, |state, cx| state.set_value(pretty(body), window, cx));
+    }
+
+    fn send(&mut self, cx: &mut Context<Self>) {
+        let Some(interaction) = self.selected_interaction() else {
+            return;
+        };
+        if time_left(&interaction).is_none() {
+            self.error = Some("The response window has closed.".to_string());
+            cx.notify();
+            return;
+        }
+        let body = match serde_json::from_str::<Value>(&self.editor.read(cx).value()) {
+            Ok(body) => body,
+            Err(e) => {
+                self.error = Some(format!("Invalid JSON: {}", e));
+                cx.notify();
+                return;
+            }
+        };
+
+        self.error = None;
+        if let Ok(mut app) = self.app.lock() {
+            let Some(stored) = app.interactions.iter_mut().find(|stored| stored.id == interaction.id) else {
+                return;
+            };
+            // The stored state, not the copy's, so a response already on its way isn't sent twice
+            match stored.state {
+                InteractionResponseState::Pending => DiscordService::respond_to_interaction(self.app.clone(), stored, body),
+                _ => DiscordService::edit_interaction_response(self.app.clone(), stored, body),
+            }
+            app.revision += 1;
+        }
+        cx.notify();
+    }
+
+    fn toggle_auto_defer(&mut self, cx: &mut Context<Self>) {
+        if let Ok(mut app) = self.app.lock() {
+            app.auto_defer_interactions = !app.auto_defer_interactions;
+        }
+        cx.notify();
+    }
+
+    fn render_details(&self, interaction: &InteractionInfo, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let open = time_left(interaction).is_some();
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .min_w(px(0.))
+            .gap_2()
+            .p_3()
+            .child(
+                div()
+                    .id("interaction-payload")
+                    .flex_1()
+                    .min_h(px(0.))
+                    .p_2()
+                    .rounded_md()
+                    .bg(gpui::rgb(0x1e1f22))
+                    .overflow_y_scrollbar()
+                    .font_family("monospace")
+                    .text_xs()
+                    .text_color(gpui::rgb(0xdbdee1))
+                    .child(pretty(&interaction.payload))
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .children(templates(interaction).into_iter().enumerate().map(|(index, (label, body))| {
+                        Button::new(("interaction-template", index))
+                            .ghost()
+                            .xsmall()
+                            .child(label)
+                            .on_click(cx.listener(move |view, _, window, cx| view.use_template(&body, window, cx)))
+                    }))
+            )
+            .child(Input::new(&self.editor))
+            .children(self.error.clone().map(|error| {
+                div().text_sm().text_color(gpui::rgb(0xf23f43)).child(error)
+            }))
+            .child(
+                div()
+                    .flex()
+                    .justify_end()
+                    .child(
+                        Button::new("interaction-send")
+                            .primary()
+                            .small()
+                            .disabled(!open)
+                            .child(match interaction.state {
+                                InteractionResponseState::Pending => "Respond",
+                                _ => "Edit response",
+                            })
+                            .on_click(cx.listener(|view, _, _, cx| view.send(cx)))
+                    )
+            )
+    }
+}
+
+impl Render for InteractionInboxView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (interactions, auto_defer) = self.app.lock()
+            .map(|app| (app.interactions.clone(), app.auto_defer_interactions))
+            .unwrap_or_default();
+        let selected = self.selected_interaction();
+
+        let mut auto_defer_button = Button::new("interaction-auto-defer")
+            .small()
+            .child("Auto-defer")
+            .tooltip("Acknowledge new interactions right away, leaving 15 minutes to answer")
+            .on_click(cx.listener(|view, _, _, cx| view.toggle_auto_defer(cx)));
+        auto_defer_button = if auto_defer {
+            auto_defer_button.primary()
+        } else {
+            auto_defer_button.ghost()
+        };
+
+        div()
+            .id("interaction-inbox")
+            .occlude()
+            .flex()
+            .flex_col()
+            .w(px(860.))
+            .h(px(620.))
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .p_3()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new("Interactions").text_color(gpui::rgb(0xf2f3f5)))
+                    .child(
+                        div()
+                            .flex()
+                            .gap_1()
+                            .child(auto_defer_button)
+                            .child(
+                                Button::new("interaction-inbox-close")
+                                    .ghost()
+                                    .small()
+                                    .child("Close")
+                                    .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                            )
+                    )
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .child(
+                        div()
+                            .id("interaction-list")
+                            .flex()
+                            .flex_col()
+                            .w(px(260.))
+                            .p_2()
+                            .gap_1()
+                            .bg(gpui::rgb(0x2b2d31))
+                            .overflow_y_scrollbar()
+                            .when(interactions.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .p_2()
+                                        .text_sm()
+                                        .text_color(gpui::rgb(0x949ba4))
+                                        .child("Interactions the bot receives show up here.")
+                                )
+                            })
+                            .children(interactions.into_iter().enumerate().map(|(index, interaction)| {
+                                let is_selected = self.selected.as_deref() == Some(interaction.id.as_str());
+                                let status = match (interaction.state, time_left(&interaction)) {
+                                    (InteractionResponseState::Responded, _) => "Responded".to_string(),
+                                    (_, None) => "Expired".to_string(),
+                                    (InteractionResponseState::Deferred, Some(left)) => format!("Deferred, {}", left),
+                                    (InteractionResponseState::Pending, Some(left)) => left,
+                                };
+                                div()
+                                    .id(("interaction", index))
+                                    .flex()
+                                    .flex_col()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_sm()
+                                    .cursor_pointer()
+                                    .when(is_selected, |this| this.bg(gpui::rgb(0x404249)))
+                                    .hover(|s| s.bg(gpui::rgb(0x35373c)))
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(gpui::rgb(0xf2f3f5))
+                                            .child(format!("{} {}", interaction.kind_name(), interaction.label))
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(gpui::rgb(0x949ba4))
+                                            .child(format!("{} · {}", interaction.user, status))
+                                    )
+                                    .on_click(cx.listener(move |view, _, window, cx| view.select(&interaction, window, cx)))
+                            }))
+                    )
+                    .children(selected.map(|interaction| self.render_details(&interaction, cx)))
+            )
+    }
+}
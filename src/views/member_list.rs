@@ -0,0 +1,289 @@
+use gpui::{
+    Context, IntoElement, ParentElement, Render, Styled, Window, div, px, prelude::*, InteractiveElement,
+};
+use gpui_component::avatar::Avatar;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use twilight_model::gateway::presence::Status;
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+use crate::app::{AppState, MemberInfo};
+use crate::services::discord::DiscordService;
+use crate::services::gateway::GatewayService;
+
+const MEMBER_RENDER_STEP: usize = 100;
+
+struct MemberGroup {
+    title: String,
+    members: Vec<MemberRow>,
+}
+
+struct MemberRow {
+    member: MemberInfo,
+    color: Option<u32>,
+    status: Status,
+}
+
+pub struct MemberListView {
+    app: Arc<Mutex<AppState>>,
+    search: gpui::Entity<InputState>,
+    visible_limit: usize,
+    _subscription: gpui::Subscription,
+}
+
+impl MemberListView {
+    pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| InputState::new(window, cx).placeholder("Search members"));
+
+        let subscription = cx.subscribe_in(&search, window, |view, search, event, _window, cx| {
+            if let InputEvent::Change = event {
+                let query = search.read(cx).value().to_string();
+                let guild_id = view.app.lock().ok().and_then(|app| app.selected_guild);
+                if let Some(guild_id) = guild_id
+                    && !query.trim().is_empty()
+                {
+                    GatewayService::search_members(&view.app, guild_id, query.trim());
+                }
+                view.visible_limit = MEMBER_RENDER_STEP;
+                cx.notify();
+            }
+        });
+
+        Self {
+            app,
+            search,
+            visible_limit: MEMBER_RENDER_STEP,
+            _subscription: subscription,
+        }
+    }
+
+    /// Shows more of the list, paging in more members once the loaded ones run out.
+    fn show_more(&mut self, cx: &mut Context<Self>) {
+        self.visible_limit += MEMBER_RENDER_STEP;
+        if let Ok(mut app) = self.app.lock()
+            && let Some(guild_id) = app.selected_guild
+        {
+            let loaded = app.members.get(&guild_id).map_or(0, |members| members.len());
+            if self.visible_limit >= loaded && app.member_pages.contains_key(&guild_id) {
+                DiscordService::fetch_member_page(&self.app, &mut app, guild_id);
+            }
+        }
+        cx.notify();
+    }
+
+    fn get_groups(app: &AppState, guild_id: Id<GuildMarker>, query: &str) -> Vec<MemberGroup> {
+        let query = query.trim().to_lowercase();
+
+        let mut hoisted: Vec<(i64, MemberGroup)> = Vec::new();
+        let mut online = MemberGroup { title: "Online".to_string(), members: Vec::new() };
+        let mut offline = MemberGroup { title: "Offline".to_string(), members: Vec::new() };
+
+        let members = app.members.get(&guild_id).map(|m| m.values()).into_iter().flatten();
+        for member in members {
+            if !query.is_empty()
+                && !member.display_name.to_lowercase().contains(&query)
+                && !member.username.to_lowercase().contains(&query)
+            {
+                continue;
+            }
+
+            let row = MemberRow {
+                member: member.clone(),
+                color: app.member_color(guild_id, member),
+                status: app.presences.get(&member.user_id).copied().unwrap_or(Status::Offline),
+            };
+
+            if !app.is_online(member.user_id) {
+                offline.members.push(row);
+            } else if let Some(role) = app.member_hoisted_role(guild_id, member) {
+                match hoisted.iter_mut().find(|(position, group)| *position == role.position && group.title == role.name) {
+                    Some((_, group)) => group.members.push(row),
+                    None => hoisted.push((role.position, MemberGroup { title: role.name, members: vec![row] })),
+                }
+            } else {
+                online.members.push(row);
+            }
+        }
+
+        hoisted.sort_by_key(|(position, _)| std::cmp::Reverse(*position));
+        let mut groups: Vec<MemberGroup> = hoisted.into_iter().map(|(_, group)| group).collect();
+        groups.push(online);
+        groups.push(offline);
+
+        for group in &mut groups {
+            group.members.sort_by_key(|row| row.member.display_name.to_lowercase());
+        }
+        groups.retain(|group| !group.members.is_empty());
+        groups
+    }
+}
+
+fn status_color(status: Status) -> u32 {
+    match status {
+        Status::Online => 0x23a55a,
+        Status::Idle => 0xf0b232,
+        Status::DoNotDisturb => 0xf23f43,
+        Status::Invisible | Status::Offline => 0x80848e,
+    }
+}
+
+fn render_member(row: MemberRow) -> impl IntoElement {
+    let avatar = if let Some(url) = &row.member.avatar_url {
+        Avatar::new()
+            .src(url.as_str())
+            .with_size(px(32.))
+    } else {
+        Avatar::new()
+            .name(&row.member.display_name)
+            .with_size(px(32.))
+    };
+    let offline = matches!(row.status, Status::Offline | Status::Invisible);
+
+    div()
+        .flex()
+        .items_center()
+        .gap_3()
+        .px_2()
+        .py_1()
+        .rounded_md()
+        .hover(|s| s.bg(gpui::rgb(0x35373c)))
+        .when(offline, |this| this.opacity(0.4))
+        .child(
+            div()
+                .relative()
+                .flex_shrink_0()
+                .child(avatar)
+                .child(
+                    div()
+                        .absolute()
+                        .bottom_0()
+                        .right_0()
+                        .size(px(10.))
+                        .rounded_full()
+                        .border_2()
+                        .border_color(gpui::rgb(0x2b2d31))
+                        .bg(gpui::rgb(status_color(row.status)))
+                )
+        )
+        .child(
+            div()
+                .flex_1()
+                .min_w(px(0.))
+                .overflow_hidden()
+                .text_ellipsis()
+                .text_color(gpui::rgb(row.color.unwrap_or(0xf2f3f5)))
+                .child(row.member.display_name.clone())
+        )
+        .when(row.member.bot, |this| {
+            this.child(
+                div()
+                    .px_1()
+                    .rounded_sm()
+                    .bg(gpui::rgb(0x5865f2))
+                    .text_xs()
+                    .text_color(gpui::rgb(0xffffff))
+                    .child("BOT")
+            )
+        })
+}
+
+impl Render for MemberListView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let query = self.search.read(cx).value().to_string();
+
+        // (loaded, total) while a large guild is still paging in
+        let (groups, partial) = self.app.lock()
+            .ok()
+            .and_then(|app| {
+                let guild_id = app.selected_guild?;
+                let groups = Self::get_groups(&app, guild_id, &query);
+                let partial = app.member_pages.get(&guild_id)
+                    .filter(|pages| !pages.complete)
+                    .map(|_| {
+                        let loaded = app.members.get(&guild_id).map_or(0, |members| members.len());
+                        let total = app.guilds.iter().find(|guild| guild.id == guild_id).and_then(|guild| guild.member_count);
+                        (loaded, total)
+                    });
+                Some((groups, partial))
+            })
+            .unwrap_or_default();
+
+        let total: usize = groups.iter().map(|group| group.members.len()).sum();
+        let has_more = total > self.visible_limit || (partial.is_some() && query.trim().is_empty());
+        let mut remaining = self.visible_limit;
+
+        div()
+            .flex()
+            .flex_col()
+            .w(px(240.))
+            .h_full()
+            .bg(gpui::rgb(0x2b2d31))
+            .child(
+                div()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Input::new(&self.search).small())
+                    .when_some(partial, |this, (loaded, total)| {
+                        this.child(
+                            div()
+                                .pt_1()
+                                .text_xs()
+                                .text_color(gpui::rgb(0x949ba4))
+                                .child(match total {
+                                    Some(total) => format!("Large server: {} of {} members loaded.", loaded, total),
+                                    None => format!("Large server: {} members loaded.", loaded),
+                                })
+                        )
+                    })
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .id("member-list")
+                    .overflow_y_scrollbar()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .p_2()
+                    .children(groups.into_iter().filter_map(|group| {
+                        if remaining == 0 {
+                            return None;
+                        }
+                        let count = group.members.len();
+                        let rows: Vec<MemberRow> = group.members.into_iter().take(remaining).collect();
+                        remaining -= rows.len();
+
+                        Some(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .child(
+                                    div()
+                                        .pt_4()
+                                        .pb_1()
+                                        .px_2()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::SEMIBOLD)
+                                        .text_color(gpui::rgb(0x949ba4))
+                                        .child(format!("{} — {}", group.title.to_uppercase(), count))
+                                )
+                                .children(rows.into_iter().map(render_member))
+                        )
+                    }))
+                    .when(has_more, |this| {
+                        this.child(
+                            Button::new("member-list-more")
+                                .ghost()
+                                .small()
+                                .w_full()
+                                .child("Show more")
+                                .on_click(cx.listener(|view, _, _, cx| view.show_more(cx)))
+                        )
+                    })
+            )
+    }
+}
@@ -0,0 +1,193 @@
+use gpui::{
+    Context, DismissEvent, EventEmitter, IntoElement, ParentElement, Render, Styled, Window, div, px,
+    prelude::*, InteractiveElement,
+};
+use gpui_component::avatar::Avatar;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use crate::app::{AppState, MentionInfo};
+use crate::services::discord::DiscordService;
+use crate::settings::Settings;
+
+/// Inbox listing recent messages that mentioned us across all guilds.
+pub struct MentionsView {
+    app: Arc<Mutex<AppState>>,
+}
+
+impl EventEmitter<DismissEvent> for MentionsView {}
+
+impl MentionsView {
+    pub fn new(app: Arc<Mutex<AppState>>, _cx: &mut Context<Self>) -> Self {
+        Self { app }
+    }
+
+    /// Mentions newest first, with the guild and channel names to show above each one.
+    fn get_mentions(&self) -> Vec<(MentionInfo, String)> {
+        self.app.lock()
+            .map(|app| {
+                app.mentions
+                    .iter()
+                    .rev()
+                    .map(|mention| {
+                        let channel = app.channel_names.get(&mention.channel_id)
+                            .map(|name| format!("#{}", name))
+                            .unwrap_or_else(|| "Direct Message".to_string());
+                        let location = match mention.guild_id.and_then(|id| app.guilds.iter().find(|g| g.id == id)) {
+                            Some(guild) => format!("{} • {}", guild.name, channel),
+                            None => channel,
+                        };
+                        let mut mention = mention.clone();
+                        if let Some(guild_id) = mention.guild_id {
+                            app.resolve_author(guild_id, &mut mention.message);
+                        }
+                        (mention, location)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn render_mention(
+    mention: MentionInfo,
+    location: String,
+    settings: &Settings,
+    app: Arc<Mutex<AppState>>,
+    cx: &mut Context<MentionsView>,
+) -> impl IntoElement + use<> {
+    let msg = mention.message;
+    let avatar = if let Some(url) = &msg.author_avatar_url {
+        Avatar::new()
+            .src(url.as_str())
+            .with_size(px(32.))
+    } else {
+        Avatar::new()
+            .name(&msg.author_name)
+            .with_size(px(32.))
+    };
+    let (guild_id, channel_id, message_id) = (mention.guild_id, mention.channel_id, msg.id);
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .p_3()
+        .rounded_md()
+        .bg(gpui::rgb(0x2b2d31))
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(
+                    div()
+                        .text_xs()
+                        .font_weight(gpui::FontWeight::SEMIBOLD)
+                        .text_color(gpui::rgb(0xb5bac1))
+                        .child(location)
+                )
+                .child(
+                    Button::new(("mention-jump", message_id.get()))
+                        .ghost()
+                        .xsmall()
+                        .child("Jump")
+                        .on_click(cx.listener(move |_, _, _, cx| {
+                            DiscordService::jump_to_message(app.clone(), guild_id, channel_id, message_id);
+                            cx.emit(DismissEvent);
+                        }))
+                )
+        )
+        .child(
+            div()
+                .flex()
+                .gap_3()
+                .child(div().flex_shrink_0().child(avatar))
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .min_w(px(0.))
+                        .child(
+                            div()
+                                .flex()
+                                .items_baseline()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(msg.author_color.unwrap_or(0xf2f3f5)))
+                                        .child(msg.author_name.clone())
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(gpui::rgb(0x949ba4))
+                                        .child(settings.format_timestamp(msg.timestamp))
+                                )
+                        )
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(gpui::rgb(0xdbdee1))
+                                .child(msg.content.clone())
+                        )
+                )
+        )
+}
+
+impl Render for MentionsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mentions = self.get_mentions();
+        let settings = self.app.lock()
+            .map(|app| app.settings.clone())
+            .unwrap_or_default();
+        let app = self.app.clone();
+
+        div()
+            .id("mentions-inbox")
+            .occlude()
+            .flex()
+            .flex_col()
+            .w(px(420.))
+            .max_h(px(560.))
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .child(
+                div()
+                    .p_4()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new("Mentions").text_color(gpui::rgb(0xf2f3f5)))
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .id("mentions-list")
+                    .overflow_y_scrollbar()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .p_2()
+                    .gap_2()
+                    .when(mentions.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_4()
+                                .text_color(gpui::rgb(0x949ba4))
+                                .child("Nobody has mentioned you yet.")
+                        )
+                    })
+                    .children(
+                        mentions
+                            .into_iter()
+                            .map(|(mention, location)| render_mention(mention, location, &settings, app.clone(), cx))
+                            .collect::<Vec<_>>()
+                    )
+            )
+    }
+}
@@ -3,3 +3,15 @@ pub mod channel_list;
 pub mod channel_messages;
 pub mod root;
 pub mod server_list;
+pub mod member_list;
+pub mod profile;
+pub mod settings;
+pub mod mentions;
+pub mod notification_settings;
+pub mod autocomplete;
+pub mod emoji_picker;
+pub mod components;
+pub mod dev_console;
+pub mod interaction_inbox;
+pub mod status_menu;
+pub mod rate_limits;
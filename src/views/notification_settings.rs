@@ -0,0 +1,218 @@
+use chrono::Utc;
+use gpui::{
+    Context, DismissEvent, EventEmitter, IntoElement, ParentElement, Render, Styled, Window, div, px,
+    prelude::*, InteractiveElement, SharedString,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use twilight_model::id::marker::{ChannelMarker, GuildMarker};
+use twilight_model::id::Id;
+use crate::app::AppState;
+use crate::services::notifications::{MuteDuration, MuteUntil, NotificationLevel, ScopeSettings};
+
+/// What the popover edits.
+#[derive(Clone, Copy, PartialEq)]
+pub enum NotificationScope {
+    Guild(Id<GuildMarker>),
+    Channel(Id<ChannelMarker>),
+}
+
+/// Mute, notification level and mention suppression for a guild or channel.
+pub struct NotificationSettingsView {
+    app: Arc<Mutex<AppState>>,
+    scope: NotificationScope,
+}
+
+impl EventEmitter<DismissEvent> for NotificationSettingsView {}
+
+impl NotificationSettingsView {
+    pub fn new(app: Arc<Mutex<AppState>>, scope: NotificationScope, _cx: &mut Context<Self>) -> Self {
+        Self { app, scope }
+    }
+
+    fn get_scope(&self) -> (String, ScopeSettings) {
+        self.app.lock()
+            .map(|app| match self.scope {
+                NotificationScope::Guild(guild_id) => {
+                    let name = app.guilds.iter()
+                        .find(|g| g.id == guild_id)
+                        .map(|g| g.name.clone())
+                        .unwrap_or_default();
+                    (name, app.notification_settings.guild(guild_id))
+                }
+                NotificationScope::Channel(channel_id) => {
+                    let name = app.channel_names.get(&channel_id)
+                        .map(|name| format!("#{}", name))
+                        .unwrap_or_default();
+                    (name, app.notification_settings.channel(channel_id))
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    fn update_scope(&mut self, cx: &mut Context<Self>, f: impl FnOnce(&mut ScopeSettings)) {
+        if let Ok(mut app) = self.app.lock() {
+            let settings = &mut app.notification_settings;
+            let scope = match self.scope {
+                NotificationScope::Guild(guild_id) => settings.guilds.entry(guild_id).or_default(),
+                NotificationScope::Channel(channel_id) => settings.channels.entry(channel_id).or_default(),
+            };
+            f(scope);
+            app.save_notification_settings();
+            app.revision += 1;
+        }
+        cx.notify();
+    }
+
+    /// A button that applies `f` to the scope's settings when clicked.
+    fn choice(
+        &self,
+        id: impl Into<SharedString>,
+        label: &'static str,
+        selected: bool,
+        cx: &mut Context<Self>,
+        f: impl Fn(&mut ScopeSettings) + 'static,
+    ) -> Button {
+        let button = Button::new(id.into())
+            .small()
+            .child(label)
+            .on_click(cx.listener(move |view, _, _, cx| view.update_scope(cx, &f)));
+
+        if selected {
+            button.primary()
+        } else {
+            button.ghost()
+        }
+    }
+}
+
+fn render_section(title: &'static str, content: impl IntoElement) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .py_2()
+        .child(
+            div()
+                .text_xs()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(gpui::rgb(0xb5bac1))
+                .child(title)
+        )
+        .child(content)
+}
+
+impl Render for NotificationSettingsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (name, scope) = self.get_scope();
+        let now = Utc::now();
+        let muted = scope.muted.filter(|mute| mute.is_active(now));
+        let is_guild = matches!(self.scope, NotificationScope::Guild(_));
+
+        let mute_status = match muted {
+            Some(MuteUntil::Forever) => Some("Muted until you turn it back on".to_string()),
+            Some(MuteUntil::Time(until)) => {
+                let settings = self.app.lock()
+                    .map(|app| app.settings.clone())
+                    .unwrap_or_default();
+                Some(format!("Muted until {}", settings.format_timestamp(until)))
+            }
+            None => None,
+        };
+
+        let mute_controls = match mute_status {
+            Some(status) => div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(div().text_sm().text_color(gpui::rgb(0xdbdee1)).child(status))
+                .child(self.choice("unmute", "Unmute", false, cx, |scope| scope.muted = None)),
+            None => div()
+                .flex()
+                .flex_wrap()
+                .gap_1()
+                .children(MuteDuration::ALL.iter().map(|duration| {
+                    let duration = *duration;
+                    self.choice(
+                        SharedString::from(format!("mute-{:?}", duration)),
+                        duration.label(),
+                        false,
+                        cx,
+                        move |scope| scope.muted = Some(duration.until(Utc::now())),
+                    )
+                })),
+        };
+
+        let levels = [
+            (None, "Default"),
+            (Some(NotificationLevel::All), "All messages"),
+            (Some(NotificationLevel::MentionsOnly), "Only mentions"),
+            (Some(NotificationLevel::Nothing), "Nothing"),
+        ];
+
+        div()
+            .id("notification-settings")
+            .occlude()
+            .w(px(360.))
+            .p_4()
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .child(
+                div()
+                    .pb_2()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new(format!("Notifications for {}", name)).text_color(gpui::rgb(0xf2f3f5)))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(gpui::rgb(0x949ba4))
+                            .child("Saved on this device only. Discord has no notification settings for bots.")
+                    )
+            )
+            .child(render_section("MUTE", mute_controls))
+            .child(render_section(
+                "NOTIFY ME ABOUT",
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .children(levels.into_iter().map(|(level, label)| {
+                        self.choice(
+                            SharedString::from(format!("level-{:?}", level)),
+                            label,
+                            scope.level == level,
+                            cx,
+                            move |scope| scope.level = level,
+                        )
+                    })),
+            ))
+            .when(is_guild, |this| {
+                this.child(render_section(
+                    "MENTIONS",
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .child(self.choice(
+                            "suppress-everyone",
+                            "Suppress @everyone and @here",
+                            scope.suppress_everyone,
+                            cx,
+                            |scope| scope.suppress_everyone = !scope.suppress_everyone,
+                        ))
+                        .child(self.choice(
+                            "suppress-roles",
+                            "Suppress all role @mentions",
+                            scope.suppress_roles,
+                            cx,
+                            |scope| scope.suppress_roles = !scope.suppress_roles,
+                        )),
+                ))
+            })
+    }
+}
@@ -0,0 +1,280 @@
+use gpui::{
+    Context, DismissEvent, EventEmitter, IntoElement, ParentElement, Render, Styled, Window, div, px,
+    prelude::*, img, ClipboardItem, InteractiveElement, ObjectFit, SharedUri,
+};
+use gpui_component::avatar::Avatar;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+use crate::app::{AppState, MemberInfo, RoleInfo, UserProfile};
+use crate::services::discord::DiscordService;
+use crate::utils::snowflake_time;
+
+/// Popout card for a user, opened from a message author's avatar or name.
+pub struct ProfileView {
+    app: Arc<Mutex<AppState>>,
+    user_id: Id<UserMarker>,
+    fallback_name: String,
+    fallback_avatar_url: Option<String>,
+}
+
+impl EventEmitter<DismissEvent> for ProfileView {}
+
+impl ProfileView {
+    pub fn new(
+        app: Arc<Mutex<AppState>>,
+        user_id: Id<UserMarker>,
+        fallback_name: String,
+        fallback_avatar_url: Option<String>,
+        _cx: &mut Context<Self>,
+    ) -> Self {
+        DiscordService::fetch_user_profile(app.clone(), user_id);
+
+        Self {
+            app,
+            user_id,
+            fallback_name,
+            fallback_avatar_url,
+        }
+    }
+
+    fn get_details(&self) -> (Option<UserProfile>, Option<MemberInfo>, Vec<RoleInfo>) {
+        self.app.lock()
+            .map(|app| {
+                let profile = app.profiles.get(&self.user_id).cloned();
+                let (member, roles) = match app.selected_guild {
+                    Some(guild_id) => {
+                        let member = app.members.get(&guild_id).and_then(|m| m.get(&self.user_id)).cloned();
+                        let mut roles: Vec<RoleInfo> = member.as_ref()
+                            .map(|member| {
+                                app.guild_roles(guild_id)
+                                    .iter()
+                                    .filter(|role| member.roles.contains(&role.id))
+                                    .cloned()
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        roles.sort_by_key(|role| std::cmp::Reverse(role.position));
+                        (member, roles)
+                    }
+                    None => (None, Vec::new()),
+                };
+                (profile, member, roles)
+            })
+            .unwrap_or((None, None, Vec::new()))
+    }
+}
+
+fn render_section(title: &'static str, content: impl IntoElement) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .text_xs()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(gpui::rgb(0xb5bac1))
+                .child(title)
+        )
+        .child(content)
+}
+
+fn render_role_chip(role: RoleInfo) -> impl IntoElement {
+    let color = if role.color == 0 { 0x80848e } else { role.color };
+
+    div()
+        .flex()
+        .items_center()
+        .gap_1()
+        .px_2()
+        .py_0p5()
+        .rounded_sm()
+        .bg(gpui::rgb(0x2b2d31))
+        .text_xs()
+        .text_color(gpui::rgb(0xdbdee1))
+        .child(div().size(px(8.)).rounded_full().bg(gpui::rgb(color)))
+        .child(role.name)
+}
+
+impl Render for ProfileView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (profile, member, roles) = self.get_details();
+
+        let display_name = member.as_ref().map(|m| m.display_name.clone())
+            .or_else(|| profile.as_ref().and_then(|p| p.global_name.clone()))
+            .or_else(|| profile.as_ref().map(|p| p.username.clone()))
+            .unwrap_or_else(|| self.fallback_name.clone());
+        let username = profile.as_ref().map(|p| p.username.clone())
+            .or_else(|| member.as_ref().map(|m| m.username.clone()));
+        let avatar_url = member.as_ref().and_then(|m| m.avatar_url.clone())
+            .or_else(|| profile.as_ref().and_then(|p| p.avatar_url.clone()))
+            .or_else(|| self.fallback_avatar_url.clone());
+        let is_bot = profile.as_ref().map(|p| p.bot)
+            .or_else(|| member.as_ref().map(|m| m.bot))
+            .unwrap_or(false);
+        let banner_url = profile.as_ref().and_then(|p| p.banner_url.clone());
+        let accent_color = profile.as_ref().and_then(|p| p.accent_color).unwrap_or(0x5865f2);
+        let pronouns = profile.as_ref().and_then(|p| p.pronouns.clone());
+        let bio = profile.as_ref().and_then(|p| p.bio.clone());
+        let created_at = snowflake_time(self.user_id.get());
+        let joined_at = member.as_ref().and_then(|m| m.joined_at);
+        let settings = self.app.lock()
+            .map(|app| app.settings.clone())
+            .unwrap_or_default();
+
+        let avatar = if let Some(url) = &avatar_url {
+            Avatar::new()
+                .src(url.as_str())
+                .with_size(px(80.))
+        } else {
+            Avatar::new()
+                .name(&display_name)
+                .with_size(px(80.))
+        };
+
+        let user_id = self.user_id;
+        let dm_name = display_name.clone();
+        let app_clone = self.app.clone();
+
+        div()
+            .id("profile-popout")
+            .occlude()
+            .w(px(300.))
+            .rounded(px(8.))
+            .overflow_hidden()
+            .bg(gpui::rgb(0x232428))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .child(
+                // Banner, falls back to the accent color
+                div()
+                    .h(px(100.))
+                    .w_full()
+                    .bg(gpui::rgb(accent_color))
+                    .when_some(banner_url, |this, url| {
+                        this.child(
+                            img(SharedUri::from(url))
+                                .size_full()
+                                .object_fit(ObjectFit::Cover)
+                        )
+                    })
+            )
+            .child(
+                div()
+                    .px_4()
+                    .mt(px(-40.))
+                    .child(
+                        div()
+                            .rounded_full()
+                            .border_4()
+                            .border_color(gpui::rgb(0x232428))
+                            .w(px(88.))
+                            .child(avatar)
+                    )
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_3()
+                    .p_4()
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .child(
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .text_xl()
+                                            .font_weight(gpui::FontWeight::BOLD)
+                                            .text_color(gpui::rgb(0xf2f3f5))
+                                            .child(display_name)
+                                    )
+                                    .when(is_bot, |this| {
+                                        this.child(
+                                            div()
+                                                .px_1()
+                                                .rounded_sm()
+                                                .bg(gpui::rgb(0x5865f2))
+                                                .text_xs()
+                                                .text_color(gpui::rgb(0xffffff))
+                                                .child("BOT")
+                                        )
+                                    })
+                            )
+                            .child(
+                                div()
+                                    .flex()
+                                    .gap_2()
+                                    .text_sm()
+                                    .text_color(gpui::rgb(0xb5bac1))
+                                    .children(username)
+                                    .when_some(pronouns, |this, pronouns| {
+                                        this.child("•").child(pronouns)
+                                    })
+                            )
+                    )
+                    .when_some(bio, |this, bio| {
+                        this.child(render_section(
+                            "ABOUT ME",
+                            div().text_sm().text_color(gpui::rgb(0xdbdee1)).child(bio),
+                        ))
+                    })
+                    .child(render_section(
+                        "MEMBER SINCE",
+                        div()
+                            .flex()
+                            .flex_col()
+                            .text_sm()
+                            .text_color(gpui::rgb(0xdbdee1))
+                            .when_some(created_at, |this, created_at| {
+                                this.child(format!("Discord: {}", settings.format_long_date(created_at)))
+                            })
+                            .when_some(joined_at, |this, joined_at| {
+                                this.child(format!("This server: {}", settings.format_long_date(joined_at)))
+                            }),
+                    ))
+                    .when(!roles.is_empty(), |this| {
+                        this.child(render_section(
+                            "ROLES",
+                            div()
+                                .flex()
+                                .flex_wrap()
+                                .gap_1()
+                                .children(roles.into_iter().map(render_role_chip)),
+                        ))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(
+                                Button::new("profile-copy-id")
+                                    .small()
+                                    .ghost()
+                                    .child("Copy User ID")
+                                    .on_click(cx.listener(move |_, _, _, cx| {
+                                        cx.write_to_clipboard(ClipboardItem::new_string(user_id.to_string()));
+                                    }))
+                            )
+                            .child(
+                                Button::new("profile-message")
+                                    .small()
+                                    .primary()
+                                    .child("Message")
+                                    .on_click(cx.listener(move |_, _, _, cx| {
+                                        DiscordService::open_dm(app_clone.clone(), user_id, dm_name.clone());
+                                        cx.emit(DismissEvent);
+                                    }))
+                            )
+                    )
+            )
+    }
+}
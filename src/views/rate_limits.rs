@@ -0,0 +1,147 @@
+use gpui::{
+    Context, DismissEvent, EventEmitter, IntoElement, ParentElement, Render, Styled, Task, Window, div, px,
+    prelude::*, InteractiveElement,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::Sizable;
+use std::time::{Duration, Instant};
+use crate::services::scheduler::{scheduler, Priority};
+
+/// Debug panel showing the request queue and the rate limits Discord reported.
+pub struct RateLimitView {
+    /// Re-renders every second so queues and reset timers stay current.
+    _ticker: Task<()>,
+}
+
+impl EventEmitter<DismissEvent> for RateLimitView {}
+
+impl RateLimitView {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let ticker = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { _ticker: ticker }
+    }
+}
+
+fn stat(label: String) -> impl IntoElement {
+    div()
+        .px_2()
+        .py_1()
+        .rounded_sm()
+        .bg(gpui::rgb(0x2b2d31))
+        .text_xs()
+        .text_color(gpui::rgb(0xdbdee1))
+        .child(label)
+}
+
+impl Render for RateLimitView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let snapshot = scheduler().snapshot();
+        let now = Instant::now();
+
+        div()
+            .id("rate-limits")
+            .occlude()
+            .flex()
+            .flex_col()
+            .w(px(620.))
+            .h(px(480.))
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .p_3()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new("Rate limits").text_color(gpui::rgb(0xf2f3f5)))
+                    .child(
+                        Button::new("rate-limits-close")
+                            .ghost()
+                            .small()
+                            .child("Close")
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                    )
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .p_3()
+                    .child(stat(format!("In flight: {}", snapshot.in_flight)))
+                    .children(Priority::ALL.iter().zip(snapshot.queued).map(|(priority, queued)| {
+                        stat(format!("{} queued: {}", priority.label(), queued))
+                    }))
+                    .child(stat(format!("Coalesced: {}", snapshot.coalesced)))
+            )
+            .child(
+                div()
+                    .id("rate-limit-buckets")
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .px_3()
+                    .pb_3()
+                    .gap_1()
+                    .overflow_y_scrollbar()
+                    .when(snapshot.buckets.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(gpui::rgb(0x949ba4))
+                                .child("Routes show up here once Discord reports their limits.")
+                        )
+                    })
+                    .children(snapshot.buckets.into_iter().map(|bucket| {
+                        let resets_in = bucket.resets_at.checked_duration_since(now);
+                        let exhausted = bucket.remaining == 0 && resets_in.is_some();
+                        let reset = match resets_in {
+                            Some(left) => format!("resets in {:.1}s", left.as_secs_f32()),
+                            None => "reset".to_string(),
+                        };
+                        let mut details = format!("{}/{} left, {}", bucket.remaining, bucket.limit, reset);
+                        if bucket.limited > 0 {
+                            details.push_str(&format!(", {} × 429", bucket.limited));
+                        }
+
+                        div()
+                            .flex()
+                            .flex_col()
+                            .px_2()
+                            .py_1()
+                            .rounded_sm()
+                            .bg(gpui::rgb(0x2b2d31))
+                            .child(
+                                div()
+                                    .font_family("monospace")
+                                    .text_xs()
+                                    .text_color(gpui::rgb(0xf2f3f5))
+                                    .child(bucket.route)
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(gpui::rgb(if exhausted { 0xf23f43 } else { 0x949ba4 }))
+                                    .child(match bucket.bucket {
+                                        Some(id) => format!("{} · bucket {}", details, id),
+                                        None => details,
+                                    })
+                            )
+                    }))
+            )
+    }
+}
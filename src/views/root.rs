@@ -29,7 +29,7 @@ impl Render for RootView {
             View::Login => self.login_view.update(cx, |view, cx| {
                 div().child(view.render(window, cx))
             }),
-            View::Servers | View::Channel => {
+            View::Channel => {
                 let channel_view = self.channel_view.get_or_insert_with(|| {
                     cx.new(|cx| ChannelView::new(window, self.app.clone(), cx))
                 });
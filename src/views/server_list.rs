@@ -1,6 +1,7 @@
 use crate::app::AppState;
 use crate::services::discord::DiscordService;
-use gpui::{Context, IntoElement, ParentElement, Render, Styled, Window, px};
+use crate::views::channel_list::render_mention_badge;
+use gpui::{Context, IntoElement, ParentElement, Render, Styled, Window, div, prelude::*, px};
 use gpui_component::label::Label;
 use gpui_component::sidebar::{Sidebar, SidebarGroup, SidebarHeader, SidebarMenu, SidebarMenuItem};
 use gpui_component::{IconName, Side};
@@ -15,10 +16,21 @@ impl ServerListView {
         Self { app }
     }
 
-    fn get_guilds(&self) -> Vec<crate::app::GuildInfo> {
+    fn get_guilds(&self) -> Vec<(crate::app::GuildInfo, bool, u32)> {
         self.app
             .lock()
-            .map(|app| app.guilds.clone())
+            .map(|app| {
+                app.guilds
+                    .iter()
+                    .map(|guild| {
+                        (
+                            guild.clone(),
+                            app.is_guild_unread(guild.id),
+                            app.guild_mention_count(guild.id),
+                        )
+                    })
+                    .collect()
+            })
             .unwrap_or_default()
     }
 
@@ -27,7 +39,7 @@ impl ServerListView {
     ) -> Option<twilight_model::id::Id<twilight_model::id::marker::GuildMarker>> {
         self.app
             .lock()
-            .map(|app| app.selected_guild.clone())
+            .map(|app| app.selected_guild)
             .unwrap_or(None)
     }
 }
@@ -47,7 +59,7 @@ impl Render for ServerListView {
                     SidebarMenu::new().children(
                         guilds
                             .iter()
-                            .map(|guild| {
+                            .map(|(guild, is_unread, mention_count)| {
                                 let guild_id = guild.id;
                                 let guild_name = guild.name.clone();
                                 let is_selected =
@@ -57,6 +69,19 @@ impl Render for ServerListView {
                                 SidebarMenuItem::new(guild_name.clone())
                                     .active(is_selected)
                                     .icon(IconName::File)
+                                    .when(*mention_count > 0, |this| {
+                                        this.suffix(render_mention_badge(*mention_count))
+                                    })
+                                    .when(*is_unread && *mention_count == 0, |this| {
+                                        // Unread pill
+                                        this.suffix(
+                                            div()
+                                                .w(px(4.))
+                                                .h(px(8.))
+                                                .rounded_r_md()
+                                                .bg(gpui::rgb(0xf2f3f5)),
+                                        )
+                                    })
                                     .on_click(cx.listener(move |_view, _, _, cx| {
                                         DiscordService::fetch_channels(app_clone.clone(), guild_id);
                                         cx.notify();
@@ -0,0 +1,276 @@
+use gpui::{
+    Context, DismissEvent, EventEmitter, IntoElement, ParentElement, Render, Styled, Window, div, px,
+    prelude::*, InteractiveElement,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use crate::app::AppState;
+use crate::services::gateway_config::{GatewayConfig, COMPRESSION, INTENT_OPTIONS, LARGE_THRESHOLD_CHOICES};
+use crate::settings::{DateOrder, MessageDisplay, Settings, TimeFormat};
+
+pub struct SettingsView {
+    app: Arc<Mutex<AppState>>,
+}
+
+impl EventEmitter<DismissEvent> for SettingsView {}
+
+impl SettingsView {
+    pub fn new(app: Arc<Mutex<AppState>>, _cx: &mut Context<Self>) -> Self {
+        Self { app }
+    }
+
+    fn get_settings(&self) -> Settings {
+        self.app.lock()
+            .map(|app| app.settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// A button that applies `f` to the settings when clicked.
+    fn choice(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        selected: bool,
+        cx: &mut Context<Self>,
+        f: impl Fn(&mut Settings) + 'static,
+    ) -> Button {
+        let button = Button::new(id)
+            .small()
+            .child(label)
+            .on_click(cx.listener(move |view, _, _, cx| view.update_settings(cx, &f)));
+
+        if selected {
+            button.primary()
+        } else {
+            button.ghost()
+        }
+    }
+
+    /// Gateway options are stored per account and used on the next connection.
+    fn update_gateway_config(&mut self, cx: &mut Context<Self>, f: impl FnOnce(&mut GatewayConfig)) {
+        if let Ok(mut app) = self.app.lock() {
+            f(&mut app.gateway_config);
+            app.save_gateway_config();
+        }
+        cx.notify();
+    }
+
+    fn render_gateway_settings(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (config, is_bot) = self.app.lock()
+            .map(|app| (app.gateway_config.clone(), app.is_bot))
+            .unwrap_or_default();
+        let intents = config.intents();
+        let privileged = config.privileged();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .pt_2()
+            .mt_2()
+            .border_t_1()
+            .border_color(gpui::rgb(0x1e1f22))
+            .child(
+                div()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(gpui::rgb(0xb5bac1))
+                    .child("GATEWAY (THIS ACCOUNT, APPLIES ON NEXT CONNECTION)")
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .children(INTENT_OPTIONS.iter().enumerate().map(|(index, (intent, label, is_privileged))| {
+                        let intent = *intent;
+                        let label = if *is_privileged { format!("{} *", label) } else { label.to_string() };
+                        let button = Button::new(("gateway-intent", index))
+                            .xsmall()
+                            .child(label)
+                            .on_click(cx.listener(move |view, _, _, cx| {
+                                view.update_gateway_config(cx, |config| config.toggle_intent(intent));
+                            }));
+                        if intents.contains(intent) { button.primary() } else { button.ghost() }
+                    }))
+            )
+            .when(is_bot && !privileged.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(gpui::rgb(0xf0b232))
+                        .child(format!(
+                            "* Privileged: {} must be enabled for the bot in the Developer Portal, or Discord closes the connection.",
+                            privileged.join(", ")
+                        ))
+                )
+            })
+            .child(render_setting(
+                "Large threshold",
+                div()
+                    .flex()
+                    .gap_1()
+                    .children(LARGE_THRESHOLD_CHOICES.into_iter().map(|threshold| {
+                        let button = Button::new(("large-threshold", threshold as usize))
+                            .small()
+                            .child(threshold.to_string())
+                            .on_click(cx.listener(move |view, _, _, cx| {
+                                view.update_gateway_config(cx, |config| config.large_threshold = threshold);
+                            }));
+                        if config.large_threshold() == threshold { button.primary() } else { button.ghost() }
+                    })),
+            ))
+            .child(render_setting(
+                "Request member lists",
+                {
+                    let enabled = config.request_member_chunks;
+                    let button = Button::new("request-member-chunks")
+                        .small()
+                        .child(if enabled { "On" } else { "Off" })
+                        .on_click(cx.listener(move |view, _, _, cx| {
+                            view.update_gateway_config(cx, |config| config.request_member_chunks = !enabled);
+                        }));
+                    if enabled { button.primary() } else { button.ghost() }
+                },
+            ))
+            .child(render_setting(
+                "Compression",
+                div()
+                    .text_sm()
+                    .text_color(gpui::rgb(0x949ba4))
+                    .child(if cfg!(feature = "zstd") {
+                        format!("{}, set when building", COMPRESSION)
+                    } else {
+                        format!("{}, set when building. Build with --features zstd for zstd-stream.", COMPRESSION)
+                    }),
+            ))
+    }
+
+    fn update_settings(&mut self, cx: &mut Context<Self>, f: impl FnOnce(&mut Settings)) {
+        if let Ok(mut app) = self.app.lock() {
+            f(&mut app.settings);
+            app.settings.save();
+            app.revision += 1;
+        }
+        cx.notify();
+    }
+}
+
+fn render_setting(title: &'static str, control: impl IntoElement) -> impl IntoElement {
+    div()
+        .flex()
+        .items_center()
+        .justify_between()
+        .gap_4()
+        .py_2()
+        .child(
+            div()
+                .text_sm()
+                .text_color(gpui::rgb(0xdbdee1))
+                .child(title)
+        )
+        .child(control)
+}
+
+impl Render for SettingsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = self.get_settings();
+
+        div()
+            .id("settings-panel")
+            .occlude()
+            .w(px(420.))
+            .p_4()
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x313338))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .pb_2()
+                    .border_b_1()
+                    .border_color(gpui::rgb(0x1e1f22))
+                    .child(Label::new("Settings").text_color(gpui::rgb(0xf2f3f5)))
+                    .child(
+                        Button::new("settings-close")
+                            .ghost()
+                            .small()
+                            .child("Close")
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+                    )
+            )
+            .child(render_setting(
+                "Message display",
+                div()
+                    .flex()
+                    .gap_1()
+                    .child(self.choice(
+                        "message-display-cozy",
+                        "Cozy",
+                        settings.message_display == MessageDisplay::Cozy,
+                        cx,
+                        |settings| settings.message_display = MessageDisplay::Cozy,
+                    ))
+                    .child(self.choice(
+                        "message-display-compact",
+                        "Compact",
+                        settings.message_display == MessageDisplay::Compact,
+                        cx,
+                        |settings| settings.message_display = MessageDisplay::Compact,
+                    )),
+            ))
+            .child(render_setting(
+                "Time format",
+                div()
+                    .flex()
+                    .gap_1()
+                    .child(self.choice(
+                        "time-format-12h",
+                        "12-hour",
+                        settings.time_format == TimeFormat::TwelveHour,
+                        cx,
+                        |settings| settings.time_format = TimeFormat::TwelveHour,
+                    ))
+                    .child(self.choice(
+                        "time-format-24h",
+                        "24-hour",
+                        settings.time_format == TimeFormat::TwentyFourHour,
+                        cx,
+                        |settings| settings.time_format = TimeFormat::TwentyFourHour,
+                    )),
+            ))
+            .child(render_setting(
+                "Date order",
+                div()
+                    .flex()
+                    .gap_1()
+                    .child(self.choice(
+                        "date-order-mdy",
+                        "MM/DD/YYYY",
+                        settings.date_order == DateOrder::MonthDayYear,
+                        cx,
+                        |settings| settings.date_order = DateOrder::MonthDayYear,
+                    ))
+                    .child(self.choice(
+                        "date-order-dmy",
+                        "DD/MM/YYYY",
+                        settings.date_order == DateOrder::DayMonthYear,
+                        cx,
+                        |settings| settings.date_order = DateOrder::DayMonthYear,
+                    ))
+                    .child(self.choice(
+                        "date-order-ymd",
+                        "YYYY-MM-DD",
+                        settings.date_order == DateOrder::YearMonthDay,
+                        cx,
+                        |settings| settings.date_order = DateOrder::YearMonthDay,
+                    )),
+            ))
+            .child(self.render_gateway_settings(cx))
+    }
+}
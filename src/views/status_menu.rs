@@ -0,0 +1,151 @@
+use gpui::{
+    Context, DismissEvent, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled, Window, div, px,
+    prelude::*, InteractiveElement,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::Sizable;
+use std::sync::{Arc, Mutex};
+use crate::app::AppState;
+use crate::services::gateway::GatewayService;
+use crate::services::presence::{ActivityKind, ActivitySetting, OwnStatus, PresenceSettings};
+
+/// Status and activity picker for the logged in account.
+pub struct StatusMenuView {
+    app: Arc<Mutex<AppState>>,
+    activity_kind: Option<ActivityKind>,
+    activity_text: Entity<InputState>,
+}
+
+impl EventEmitter<DismissEvent> for StatusMenuView {}
+
+impl StatusMenuView {
+    pub fn new(window: &mut Window, app: Arc<Mutex<AppState>>, cx: &mut Context<Self>) -> Self {
+        let activity = app.lock().ok().and_then(|app| app.presence.activity.clone());
+        let activity_text = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).placeholder("What are you up to?");
+            if let Some(activity) = &activity {
+                state.set_value(activity.text.clone(), window, cx);
+            }
+            state
+        });
+
+        Self {
+            app,
+            activity_kind: activity.map(|activity| activity.kind),
+            activity_text,
+        }
+    }
+
+    /// Saves the change for this account and sends it if connected.
+    fn change_presence(&self, change: impl FnOnce(&mut PresenceSettings)) {
+        if let Ok(mut app) = self.app.lock() {
+            change(&mut app.presence);
+            app.save_presence();
+            GatewayService::send_presence(&app);
+            app.revision += 1;
+        }
+    }
+
+    fn set_status(&mut self, status: OwnStatus, cx: &mut Context<Self>) {
+        self.change_presence(|presence| presence.status = status);
+        cx.emit(DismissEvent);
+    }
+
+    fn save_activity(&mut self, cx: &mut Context<Self>) {
+        let text = self.activity_text.read(cx).value().trim().to_string();
+        let activity = self.activity_kind
+            .filter(|_| !text.is_empty())
+            .map(|kind| ActivitySetting { kind, text });
+        self.change_presence(|presence| presence.activity = activity);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for StatusMenuView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = self.app.lock().map(|app| app.presence.status).unwrap_or_default();
+
+        div()
+            .id("status-menu")
+            .occlude()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .w(px(300.))
+            .p_2()
+            .rounded(px(8.))
+            .bg(gpui::rgb(0x111214))
+            .shadow_lg()
+            .on_mouse_down_out(cx.listener(|_, _, _, cx| cx.emit(DismissEvent)))
+            .children(OwnStatus::ALL.into_iter().map(|status| {
+                div()
+                    .id(("own-status", status as usize))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .py_1p5()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .when(status == current, |this| this.bg(gpui::rgb(0x404249)))
+                    .hover(|s| s.bg(gpui::rgb(0x4752c4)))
+                    .on_click(cx.listener(move |view, _, _, cx| view.set_status(status, cx)))
+                    .child(div().size(px(10.)).rounded_full().bg(gpui::rgb(status.color())))
+                    .child(div().text_sm().text_color(gpui::rgb(0xdbdee1)).child(status.label()))
+            }))
+            .child(div().my_1().h(px(1.)).bg(gpui::rgb(0x2e2f34)))
+            .child(
+                div()
+                    .px_2()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(gpui::rgb(0xb5bac1))
+                    .child("ACTIVITY")
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .px_2()
+                    .child({
+                        let button = Button::new("activity-none")
+                            .xsmall()
+                            .child("None")
+                            .on_click(cx.listener(|view, _, _, cx| {
+                                view.activity_kind = None;
+                                cx.notify();
+                            }));
+                        if self.activity_kind.is_none() { button.primary() } else { button.ghost() }
+                    })
+                    .children(ActivityKind::ALL.into_iter().map(|kind| {
+                        let button = Button::new(("activity-kind", kind as usize))
+                            .xsmall()
+                            .child(kind.label())
+                            .on_click(cx.listener(move |view, _, _, cx| {
+                                view.activity_kind = Some(kind);
+                                cx.notify();
+                            }));
+                        if self.activity_kind == Some(kind) { button.primary() } else { button.ghost() }
+                    }))
+            )
+            .when(self.activity_kind.is_some(), |this| {
+                this.child(div().px_2().child(Input::new(&self.activity_text).small()))
+            })
+            .child(
+                div()
+                    .flex()
+                    .justify_end()
+                    .px_2()
+                    .pt_1()
+                    .child(
+                        Button::new("activity-save")
+                            .primary()
+                            .small()
+                            .child("Save activity")
+                            .on_click(cx.listener(|view, _, _, cx| view.save_activity(cx)))
+                    )
+            )
+    }
+}